@@ -0,0 +1,430 @@
+use serde::{Serialize, Deserialize};
+use serde_json::{json, Value};
+use solana_sdk::{program_option::COption, program_pack::Pack, pubkey::Pubkey};
+use solana_address_lookup_table_interface::state::AddressLookupTable;
+use solana_nonce::{state::State as NonceState, versions::Versions as NonceVersions};
+use solana_stake_interface::state::{Meta, StakeStateV2};
+use spl_token_2022::extension::{self, BaseStateWithExtensions, ExtensionType, StateWithExtensions};
+use spl_token_2022::instruction::MAX_SIGNERS;
+use spl_token_2022::state::{Account, AccountState, Mint, Multisig};
+use spl_token_group_interface::state::{TokenGroup, TokenGroupMember};
+use spl_token_metadata_interface::state::TokenMetadata;
+
+use crate::encoding::Encoding;
+use crate::{error_response, json_response, parse_pubkey, JsonResponse};
+
+#[derive(Deserialize)]
+struct DecodeAccountRequest {
+    owner: String,
+    data: String,
+    #[serde(rename = "dataEncoding", default)]
+    data_encoding: Option<String>,
+}
+
+#[derive(Serialize)]
+struct DecodeAccountResponse {
+    success: bool,
+    data: DecodedAccount,
+}
+
+#[derive(Serialize)]
+struct DecodedAccount {
+    program: &'static str,
+    #[serde(rename = "type")]
+    account_type: &'static str,
+    info: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    extensions: Option<Vec<Value>>,
+}
+
+impl DecodedAccount {
+    fn new(program: &'static str, account_type: &'static str, info: Value) -> Self {
+        DecodedAccount { program, account_type, info, extensions: None }
+    }
+}
+
+pub fn decode(content: &str) -> JsonResponse {
+    let data: DecodeAccountRequest = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    let owner = match parse_pubkey(&data.owner, "owner") {
+        Ok(owner) => owner,
+        Err(error) => return error_response(400, &error),
+    };
+
+    let encoding = match Encoding::parse_or(data.data_encoding.as_deref(), Encoding::Base64) {
+        Ok(encoding) => encoding,
+        Err(error) => return error_response(400, &error),
+    };
+    let bytes = match encoding.decode(&data.data) {
+        Some(bytes) => bytes,
+        None => return error_response(400, "Invalid data encoding"),
+    };
+
+    let decoded = if owner == spl_token::ID {
+        decode_token(&bytes)
+    } else if owner == spl_token_2022::ID {
+        decode_token_2022(&bytes)
+    } else if owner == solana_sdk_ids::system_program::ID {
+        decode_nonce(&bytes)
+    } else if owner == solana_stake_interface::program::ID {
+        decode_stake(&bytes)
+    } else if owner == solana_address_lookup_table_interface::program::ID {
+        decode_lookup_table(&bytes)
+    } else {
+        return error_response(400, "Unsupported account owner");
+    };
+
+    match decoded {
+        Ok(data) => json_response(200, serde_json::to_string(&DecodeAccountResponse { success: true, data }).unwrap()),
+        Err(error) => error_response(400, &error),
+    }
+}
+
+// The original token program only ever holds fixed-size accounts, so the
+// length alone tells the three layouts apart.
+fn decode_token(bytes: &[u8]) -> Result<DecodedAccount, String> {
+    match bytes.len() {
+        Mint::LEN => Mint::unpack(bytes)
+            .map(|mint| DecodedAccount::new("spl-token", "mint", mint_info(&mint)))
+            .map_err(|_| "Mint is not initialized".to_string()),
+        Account::LEN => Account::unpack(bytes)
+            .map(|account| DecodedAccount::new("spl-token", "account", account_info(&account)))
+            .map_err(|_| "Token account is not initialized".to_string()),
+        Multisig::LEN => decode_multisig("spl-token", bytes),
+        len => Err(format!("Unexpected token account length: {} bytes", len)),
+    }
+}
+
+// Token-2022 mints and accounts carry a type byte and TLV extensions after
+// the base state; multisigs keep the original layout.
+fn decode_token_2022(bytes: &[u8]) -> Result<DecodedAccount, String> {
+    if bytes.len() == Multisig::LEN {
+        return decode_multisig("spl-token-2022", bytes);
+    }
+
+    if let Ok(state) = StateWithExtensions::<Account>::unpack(bytes) {
+        return Ok(DecodedAccount {
+            extensions: Some(extensions(&state)?),
+            ..DecodedAccount::new("spl-token-2022", "account", account_info(&state.base))
+        });
+    }
+
+    match StateWithExtensions::<Mint>::unpack(bytes) {
+        Ok(state) => Ok(DecodedAccount {
+            extensions: Some(extensions(&state)?),
+            ..DecodedAccount::new("spl-token-2022", "mint", mint_info(&state.base))
+        }),
+        Err(_) => Err("Data is not an initialized Token-2022 mint, account or multisig".to_string()),
+    }
+}
+
+// `Multisig::unpack` only checks the initialized flag, so a signer count past
+// the fixed signer array has to be rejected here.
+fn decode_multisig(program: &'static str, bytes: &[u8]) -> Result<DecodedAccount, String> {
+    let multisig = Multisig::unpack(bytes).map_err(|_| "Multisig is not initialized".to_string())?;
+    if usize::from(multisig.n) > MAX_SIGNERS || multisig.m > multisig.n {
+        return Err("Multisig has an invalid signer count".to_string());
+    }
+    Ok(DecodedAccount::new(program, "multisig", multisig_info(&multisig)))
+}
+
+fn decode_nonce(bytes: &[u8]) -> Result<DecodedAccount, String> {
+    let versions: NonceVersions = bincode::deserialize(bytes).map_err(|_| "Data is not a nonce account".to_string())?;
+    let version = match versions {
+        NonceVersions::Legacy(_) => "legacy",
+        NonceVersions::Current(_) => "current",
+    };
+
+    match versions.state() {
+        NonceState::Uninitialized => Ok(DecodedAccount::new("nonce", "uninitialized", json!({ "version": version }))),
+        NonceState::Initialized(data) => Ok(DecodedAccount::new("nonce", "initialized", json!({
+            "version": version,
+            "authority": data.authority.to_string(),
+            "blockhash": data.blockhash().to_string(),
+            "lamports_per_signature": data.get_lamports_per_signature(),
+        }))),
+    }
+}
+
+fn decode_stake(bytes: &[u8]) -> Result<DecodedAccount, String> {
+    let state: StakeStateV2 = bincode::deserialize(bytes).map_err(|_| "Data is not a stake account".to_string())?;
+
+    Ok(match state {
+        StakeStateV2::Uninitialized => DecodedAccount::new("stake", "uninitialized", json!({})),
+        StakeStateV2::Initialized(meta) => DecodedAccount::new("stake", "initialized", json!({ "meta": meta_info(&meta) })),
+        StakeStateV2::Stake(meta, stake, _) => DecodedAccount::new("stake", "delegated", json!({
+            "meta": meta_info(&meta),
+            "stake": {
+                "delegation": {
+                    "voter": stake.delegation.voter_pubkey.to_string(),
+                    "stake": stake.delegation.stake,
+                    "activation_epoch": stake.delegation.activation_epoch,
+                    "deactivation_epoch": stake.delegation.deactivation_epoch,
+                },
+                "credits_observed": stake.credits_observed,
+            },
+        })),
+        StakeStateV2::RewardsPool => DecodedAccount::new("stake", "rewardsPool", json!({})),
+    })
+}
+
+fn decode_lookup_table(bytes: &[u8]) -> Result<DecodedAccount, String> {
+    let table = AddressLookupTable::deserialize(bytes).map_err(|_| "Data is not an address lookup table".to_string())?;
+    let meta = &table.meta;
+
+    Ok(DecodedAccount::new("address-lookup-table", "lookupTable", json!({
+        // u64::MAX marks a table that has never been deactivated
+        "deactivation_slot": (meta.deactivation_slot != u64::MAX).then_some(meta.deactivation_slot),
+        "last_extended_slot": meta.last_extended_slot,
+        "last_extended_slot_start_index": meta.last_extended_slot_start_index,
+        "authority": meta.authority.map(|authority| authority.to_string()),
+        "addresses": table.addresses.iter().map(Pubkey::to_string).collect::<Vec<_>>(),
+    })))
+}
+
+fn mint_info(mint: &Mint) -> Value {
+    json!({
+        "mint_authority": coption_address(&mint.mint_authority),
+        "supply": mint.supply,
+        "decimals": mint.decimals,
+        "is_initialized": mint.is_initialized,
+        "freeze_authority": coption_address(&mint.freeze_authority),
+    })
+}
+
+fn account_info(account: &Account) -> Value {
+    json!({
+        "mint": account.mint.to_string(),
+        "owner": account.owner.to_string(),
+        "amount": account.amount,
+        "delegate": coption_address(&account.delegate),
+        "state": account_state(account.state),
+        "is_native": account.is_native.is_some(),
+        "rent_exempt_reserve": Option::<u64>::from(account.is_native),
+        "delegated_amount": account.delegated_amount,
+        "close_authority": coption_address(&account.close_authority),
+    })
+}
+
+fn multisig_info(multisig: &Multisig) -> Value {
+    json!({
+        "num_required_signers": multisig.m,
+        "num_valid_signers": multisig.n,
+        "is_initialized": multisig.is_initialized,
+        "signers": multisig.signers[..usize::from(multisig.n)].iter().map(Pubkey::to_string).collect::<Vec<_>>(),
+    })
+}
+
+fn meta_info(meta: &Meta) -> Value {
+    json!({
+        "rent_exempt_reserve": meta.rent_exempt_reserve,
+        "authorized": {
+            "staker": meta.authorized.staker.to_string(),
+            "withdrawer": meta.authorized.withdrawer.to_string(),
+        },
+        "lockup": {
+            "unix_timestamp": meta.lockup.unix_timestamp,
+            "epoch": meta.lockup.epoch,
+            "custodian": meta.lockup.custodian.to_string(),
+        },
+    })
+}
+
+fn account_state(state: AccountState) -> &'static str {
+    match state {
+        AccountState::Uninitialized => "uninitialized",
+        AccountState::Initialized => "initialized",
+        AccountState::Frozen => "frozen",
+    }
+}
+
+pub(crate) fn coption_address(value: &COption<Pubkey>) -> Option<String> {
+    Option::<&Pubkey>::from(value.as_ref()).map(Pubkey::to_string)
+}
+
+fn optional_address<T: Into<Option<Pubkey>>>(value: T) -> Option<String> {
+    value.into().map(|pubkey| pubkey.to_string())
+}
+
+// Lists every extension in the TLV data, with the fields of the ones this
+// server knows how to read; confidential-transfer state is reported by name only.
+fn extensions<S: BaseStateWithExtensions<T>, T: extension::BaseState>(state: &S) -> Result<Vec<Value>, String> {
+    let types = state.get_extension_types().map_err(|_| "Invalid extension data".to_string())?;
+    let invalid = |_| "Invalid extension data".to_string();
+
+    types.into_iter().map(|extension_type| {
+        let details = match extension_type {
+            ExtensionType::TransferFeeConfig => {
+                let config = state.get_extension::<extension::transfer_fee::TransferFeeConfig>().map_err(invalid)?;
+                let fee = |fee: &extension::transfer_fee::TransferFee| json!({
+                    "epoch": u64::from(fee.epoch),
+                    "maximum_fee": u64::from(fee.maximum_fee),
+                    "transfer_fee_basis_points": u16::from(fee.transfer_fee_basis_points),
+                });
+                json!({
+                    "transfer_fee_config_authority": optional_address(config.transfer_fee_config_authority),
+                    "withdraw_withheld_authority": optional_address(config.withdraw_withheld_authority),
+                    "withheld_amount": u64::from(config.withheld_amount),
+                    "older_transfer_fee": fee(&config.older_transfer_fee),
+                    "newer_transfer_fee": fee(&config.newer_transfer_fee),
+                })
+            }
+            ExtensionType::TransferFeeAmount => {
+                let amount = state.get_extension::<extension::transfer_fee::TransferFeeAmount>().map_err(invalid)?;
+                json!({ "withheld_amount": u64::from(amount.withheld_amount) })
+            }
+            ExtensionType::MintCloseAuthority => {
+                let close = state.get_extension::<extension::mint_close_authority::MintCloseAuthority>().map_err(invalid)?;
+                json!({ "close_authority": optional_address(close.close_authority) })
+            }
+            ExtensionType::DefaultAccountState => {
+                let default = state.get_extension::<extension::default_account_state::DefaultAccountState>().map_err(invalid)?;
+                let default_state = AccountState::try_from(default.state).map_err(|_| "Invalid extension data".to_string())?;
+                json!({ "account_state": account_state(default_state) })
+            }
+            ExtensionType::MemoTransfer => {
+                let memo = state.get_extension::<extension::memo_transfer::MemoTransfer>().map_err(invalid)?;
+                json!({ "require_incoming_transfer_memos": bool::from(memo.require_incoming_transfer_memos) })
+            }
+            ExtensionType::InterestBearingConfig => {
+                let config = state.get_extension::<extension::interest_bearing_mint::InterestBearingConfig>().map_err(invalid)?;
+                json!({
+                    "rate_authority": optional_address(config.rate_authority),
+                    "initialization_timestamp": i64::from(config.initialization_timestamp),
+                    "pre_update_average_rate": i16::from(config.pre_update_average_rate),
+                    "last_update_timestamp": i64::from(config.last_update_timestamp),
+                    "current_rate": i16::from(config.current_rate),
+                })
+            }
+            ExtensionType::CpiGuard => {
+                let guard = state.get_extension::<extension::cpi_guard::CpiGuard>().map_err(invalid)?;
+                json!({ "lock_cpi": bool::from(guard.lock_cpi) })
+            }
+            ExtensionType::PermanentDelegate => {
+                let delegate = state.get_extension::<extension::permanent_delegate::PermanentDelegate>().map_err(invalid)?;
+                json!({ "delegate": optional_address(delegate.delegate) })
+            }
+            ExtensionType::TransferHook => {
+                let hook = state.get_extension::<extension::transfer_hook::TransferHook>().map_err(invalid)?;
+                json!({
+                    "authority": optional_address(hook.authority),
+                    "program_id": optional_address(hook.program_id),
+                })
+            }
+            ExtensionType::TransferHookAccount => {
+                let hook = state.get_extension::<extension::transfer_hook::TransferHookAccount>().map_err(invalid)?;
+                json!({ "transferring": bool::from(hook.transferring) })
+            }
+            ExtensionType::MetadataPointer => {
+                let pointer = state.get_extension::<extension::metadata_pointer::MetadataPointer>().map_err(invalid)?;
+                json!({
+                    "authority": optional_address(pointer.authority),
+                    "metadata_address": optional_address(pointer.metadata_address),
+                })
+            }
+            ExtensionType::TokenMetadata => {
+                let metadata = state.get_variable_len_extension::<TokenMetadata>().map_err(invalid)?;
+                json!({
+                    "update_authority": optional_address(metadata.update_authority),
+                    "mint": metadata.mint.to_string(),
+                    "name": metadata.name,
+                    "symbol": metadata.symbol,
+                    "uri": metadata.uri,
+                    "additional_metadata": metadata.additional_metadata,
+                })
+            }
+            ExtensionType::GroupPointer => {
+                let pointer = state.get_extension::<extension::group_pointer::GroupPointer>().map_err(invalid)?;
+                json!({
+                    "authority": optional_address(pointer.authority),
+                    "group_address": optional_address(pointer.group_address),
+                })
+            }
+            ExtensionType::TokenGroup => {
+                let group = state.get_extension::<TokenGroup>().map_err(invalid)?;
+                json!({
+                    "update_authority": optional_address(group.update_authority),
+                    "mint": group.mint.to_string(),
+                    "size": u64::from(group.size),
+                    "max_size": u64::from(group.max_size),
+                })
+            }
+            ExtensionType::GroupMemberPointer => {
+                let pointer = state.get_extension::<extension::group_member_pointer::GroupMemberPointer>().map_err(invalid)?;
+                json!({
+                    "authority": optional_address(pointer.authority),
+                    "member_address": optional_address(pointer.member_address),
+                })
+            }
+            ExtensionType::TokenGroupMember => {
+                let member = state.get_extension::<TokenGroupMember>().map_err(invalid)?;
+                json!({
+                    "mint": member.mint.to_string(),
+                    "group": member.group.to_string(),
+                    "member_number": u64::from(member.member_number),
+                })
+            }
+            ExtensionType::ScaledUiAmount => {
+                let config = state.get_extension::<extension::scaled_ui_amount::ScaledUiAmountConfig>().map_err(invalid)?;
+                json!({
+                    "authority": optional_address(config.authority),
+                    "multiplier": f64::from(config.multiplier),
+                    "new_multiplier_effective_timestamp": i64::from(config.new_multiplier_effective_timestamp),
+                    "new_multiplier": f64::from(config.new_multiplier),
+                })
+            }
+            ExtensionType::Pausable => {
+                let config = state.get_extension::<extension::pausable::PausableConfig>().map_err(invalid)?;
+                json!({
+                    "authority": optional_address(config.authority),
+                    "paused": bool::from(config.paused),
+                })
+            }
+            _ => Value::Null,
+        };
+
+        let mut entry = json!({ "extension": extension_name(extension_type) });
+        if !details.is_null() {
+            entry["state"] = details;
+        }
+        Ok(entry)
+    }).collect()
+}
+
+// `TransferFeeConfig` becomes `transferFeeConfig`, matching RPC jsonParsed output
+pub(crate) fn extension_name(extension_type: ExtensionType) -> String {
+    let name = format!("{:?}", extension_type);
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // m, n, is_initialized, then the fixed array of eleven signer keys
+    fn multisig_bytes(m: u8, n: u8) -> Vec<u8> {
+        let mut bytes = vec![0; Multisig::LEN];
+        bytes[..3].copy_from_slice(&[m, n, 1]);
+        bytes
+    }
+
+    #[test]
+    fn multisig_signer_count_past_array_is_rejected() {
+        let bytes = multisig_bytes(1, 200);
+        assert!(decode_token(&bytes).is_err());
+        assert!(decode_token_2022(&bytes).is_err());
+    }
+
+    #[test]
+    fn multisig_lists_only_valid_signers() {
+        let decoded = decode_token(&multisig_bytes(2, 3)).unwrap();
+        assert_eq!(decoded.info["signers"].as_array().unwrap().len(), 3);
+    }
+}
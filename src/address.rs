@@ -0,0 +1,287 @@
+use std::str::FromStr;
+use serde::{Serialize, Deserialize};
+use solana_sdk::pubkey::{Pubkey, MAX_SEEDS, MAX_SEED_LEN};
+use solana_sdk::pubkey;
+use solana_sdk_ids::{self as sdk_ids, sysvar};
+
+use crate::{error_response, json_response, JsonResponse};
+
+#[derive(Deserialize)]
+struct PdaRequest {
+    #[serde(rename = "programId")]
+    program_id: String,
+    seeds: Vec<Seed>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", content = "value")]
+enum Seed {
+    #[serde(rename = "utf8")]
+    Utf8(String),
+    #[serde(rename = "pubkey")]
+    Pubkey(String),
+    #[serde(rename = "hex")]
+    Hex(String),
+    #[serde(rename = "u64le")]
+    U64Le(u64),
+}
+
+#[derive(Serialize)]
+struct PdaResponse {
+    success: bool,
+    data: PdaData,
+}
+
+#[derive(Serialize)]
+struct PdaData {
+    address: String,
+    bump: u8,
+    program_id: String,
+}
+
+#[derive(Deserialize)]
+struct WithSeedRequest {
+    base: String,
+    seed: String,
+    #[serde(rename = "programId")]
+    program_id: String,
+}
+
+#[derive(Serialize)]
+struct WithSeedResponse {
+    success: bool,
+    data: WithSeedData,
+}
+
+#[derive(Serialize)]
+struct WithSeedData {
+    address: String,
+    base: String,
+    seed: String,
+    program_id: String,
+}
+
+#[derive(Deserialize)]
+struct OnCurveRequest {
+    address: String,
+}
+
+#[derive(Serialize)]
+struct OnCurveResponse {
+    success: bool,
+    data: OnCurveData,
+}
+
+#[derive(Serialize)]
+struct OnCurveData {
+    address: String,
+    on_curve: bool,
+}
+
+#[derive(Deserialize)]
+struct InspectRequest {
+    address: String,
+}
+
+#[derive(Serialize)]
+struct InspectResponse {
+    success: bool,
+    data: InspectData,
+}
+
+#[derive(Serialize)]
+struct InspectData {
+    address: String,
+    valid_base58: bool,
+    byte_length: Option<usize>,
+    valid_pubkey: bool,
+    on_curve: Option<bool>,
+    known: Option<KnownAddress>,
+}
+
+#[derive(Serialize, Clone, Copy)]
+pub struct KnownAddress {
+    name: &'static str,
+    kind: &'static str,
+}
+
+const fn program(name: &'static str) -> KnownAddress {
+    KnownAddress { name, kind: "program" }
+}
+
+const fn sysvar(name: &'static str) -> KnownAddress {
+    KnownAddress { name, kind: "sysvar" }
+}
+
+const KNOWN_ADDRESSES: &[(Pubkey, KnownAddress)] = &[
+    (sdk_ids::system_program::ID, program("System Program")),
+    (spl_token::ID, program("Token Program")),
+    (pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb"), program("Token-2022 Program")),
+    (crate::ASSOCIATED_TOKEN_PROGRAM_ID, program("Associated Token Account Program")),
+    (crate::memo::MEMO_PROGRAM_ID, program("Memo Program")),
+    (pubkey!("Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo"), program("Memo Program (v1)")),
+    (sdk_ids::stake::ID, program("Stake Program")),
+    (sdk_ids::stake::config::ID, program("Stake Config")),
+    (sdk_ids::vote::ID, program("Vote Program")),
+    (sdk_ids::compute_budget::ID, program("Compute Budget Program")),
+    (sdk_ids::address_lookup_table::ID, program("Address Lookup Table Program")),
+    (sdk_ids::bpf_loader_upgradeable::ID, program("BPF Upgradeable Loader")),
+    (sdk_ids::bpf_loader::ID, program("BPF Loader")),
+    (sdk_ids::ed25519_program::ID, program("Ed25519 Program")),
+    (sdk_ids::secp256k1_program::ID, program("Secp256k1 Program")),
+    (sdk_ids::native_loader::ID, program("Native Loader")),
+    (sdk_ids::incinerator::ID, program("Incinerator")),
+    (sysvar::ID, sysvar("Sysvar Owner")),
+    (sysvar::clock::ID, sysvar("Clock Sysvar")),
+    (sysvar::epoch_rewards::ID, sysvar("Epoch Rewards Sysvar")),
+    (sysvar::epoch_schedule::ID, sysvar("Epoch Schedule Sysvar")),
+    (sysvar::fees::ID, sysvar("Fees Sysvar")),
+    (sysvar::instructions::ID, sysvar("Instructions Sysvar")),
+    (sysvar::last_restart_slot::ID, sysvar("Last Restart Slot Sysvar")),
+    (sysvar::recent_blockhashes::ID, sysvar("Recent Blockhashes Sysvar")),
+    (sysvar::rent::ID, sysvar("Rent Sysvar")),
+    (sysvar::rewards::ID, sysvar("Rewards Sysvar")),
+    (sysvar::slot_hashes::ID, sysvar("Slot Hashes Sysvar")),
+    (sysvar::slot_history::ID, sysvar("Slot History Sysvar")),
+    (sysvar::stake_history::ID, sysvar("Stake History Sysvar")),
+];
+
+pub fn known_address(address: &Pubkey) -> Option<KnownAddress> {
+    KNOWN_ADDRESSES.iter().find(|(id, _)| id == address).map(|(_, known)| *known)
+}
+
+impl Seed {
+    fn to_bytes(&self) -> Result<Vec<u8>, &'static str> {
+        match self {
+            Seed::Utf8(value) => Ok(value.as_bytes().to_vec()),
+            Seed::Pubkey(value) => Pubkey::from_str(value)
+                .map(|pk| pk.to_bytes().to_vec())
+                .map_err(|_| "Invalid pubkey seed"),
+            Seed::Hex(value) => hex::decode(value).map_err(|_| "Invalid hex seed"),
+            Seed::U64Le(value) => Ok(value.to_le_bytes().to_vec()),
+        }
+    }
+}
+
+pub fn pda(content: &str) -> JsonResponse {
+    let data: PdaRequest = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    let program_id = match Pubkey::from_str(&data.program_id) {
+        Ok(pk) => pk,
+        Err(_) => return error_response(400, "Invalid program id"),
+    };
+
+    // The bump seed takes the last of the MAX_SEEDS slots
+    if data.seeds.len() >= MAX_SEEDS {
+        return error_response(400, "Too many seeds");
+    }
+
+    let mut seeds = Vec::with_capacity(data.seeds.len());
+    for seed in &data.seeds {
+        match seed.to_bytes() {
+            Ok(bytes) if bytes.len() > MAX_SEED_LEN => return error_response(400, "Seed exceeds 32 bytes"),
+            Ok(bytes) => seeds.push(bytes),
+            Err(error) => return error_response(400, error),
+        }
+    }
+
+    let seed_refs = seeds.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    let (address, bump) = match Pubkey::try_find_program_address(&seed_refs, &program_id) {
+        Some(found) => found,
+        None => return error_response(400, "Unable to find a viable program address bump seed"),
+    };
+
+    let response_data = PdaResponse {
+        success: true,
+        data: PdaData {
+            address: address.to_string(),
+            bump,
+            program_id: program_id.to_string(),
+        },
+    };
+
+    json_response(200, serde_json::to_string(&response_data).unwrap())
+}
+
+pub fn with_seed(content: &str) -> JsonResponse {
+    let data: WithSeedRequest = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    let base = match Pubkey::from_str(&data.base) {
+        Ok(pk) => pk,
+        Err(_) => return error_response(400, "Invalid base address"),
+    };
+
+    let program_id = match Pubkey::from_str(&data.program_id) {
+        Ok(pk) => pk,
+        Err(_) => return error_response(400, "Invalid program id"),
+    };
+
+    let address = match Pubkey::create_with_seed(&base, &data.seed, &program_id) {
+        Ok(address) => address,
+        Err(err) => return error_response(400, &err.to_string()),
+    };
+
+    let response_data = WithSeedResponse {
+        success: true,
+        data: WithSeedData {
+            address: address.to_string(),
+            base: base.to_string(),
+            seed: data.seed,
+            program_id: program_id.to_string(),
+        },
+    };
+
+    json_response(200, serde_json::to_string(&response_data).unwrap())
+}
+
+pub fn on_curve(content: &str) -> JsonResponse {
+    let data: OnCurveRequest = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    let address = match Pubkey::from_str(&data.address) {
+        Ok(pk) => pk,
+        Err(_) => return error_response(400, "Invalid address"),
+    };
+
+    let response_data = OnCurveResponse {
+        success: true,
+        data: OnCurveData {
+            address: address.to_string(),
+            on_curve: address.is_on_curve(),
+        },
+    };
+
+    json_response(200, serde_json::to_string(&response_data).unwrap())
+}
+
+pub fn inspect(content: &str) -> JsonResponse {
+    let data: InspectRequest = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    let decoded = bs58::decode(&data.address).into_vec().ok();
+    let pubkey = decoded.as_deref().and_then(|bytes| Pubkey::try_from(bytes).ok());
+
+    let response_data = InspectResponse {
+        success: true,
+        data: InspectData {
+            valid_base58: decoded.is_some(),
+            byte_length: decoded.as_ref().map(Vec::len),
+            valid_pubkey: pubkey.is_some(),
+            on_curve: pubkey.map(|pk| pk.is_on_curve()),
+            known: pubkey.as_ref().and_then(known_address),
+            address: data.address,
+        },
+    };
+
+    json_response(200, serde_json::to_string(&response_data).unwrap())
+}
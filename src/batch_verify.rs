@@ -0,0 +1,201 @@
+use serde::{Serialize, Deserialize};
+use rayon::prelude::*;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+
+use crate::{decode_signature_and_pubkey, error_response, json_response, message_encodings, JsonResponse};
+
+const MAX_BATCH_ITEMS: usize = 10_000;
+const BATCH_CHUNK_SIZE: usize = 64;
+
+#[derive(Deserialize)]
+struct VerifyBatchRequest {
+    items: Vec<VerifyItem>,
+    #[serde(rename = "messageEncoding", default)]
+    message_encoding: Option<String>,
+    #[serde(rename = "signatureEncoding", default)]
+    signature_encoding: Option<String>,
+    #[serde(default)]
+    cofactored: bool,
+}
+
+#[derive(Deserialize)]
+struct VerifyItem {
+    message: String,
+    signature: String,
+    pubkey: String,
+    #[serde(rename = "messageEncoding", default)]
+    message_encoding: Option<String>,
+    #[serde(rename = "signatureEncoding", default)]
+    signature_encoding: Option<String>,
+}
+
+#[derive(Serialize)]
+struct VerifyBatchResponse {
+    success: bool,
+    data: VerifyBatchData,
+}
+
+#[derive(Serialize)]
+struct VerifyBatchData {
+    valid_count: usize,
+    invalid_count: usize,
+    results: Vec<VerifyResult>,
+}
+
+#[derive(Serialize)]
+struct VerifyResult {
+    index: usize,
+    valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+struct DecodedItem {
+    index: usize,
+    message: Vec<u8>,
+    signature: Signature,
+    pubkey: Pubkey,
+}
+
+fn decode_item(index: usize, item: &VerifyItem, request: &VerifyBatchRequest) -> Result<DecodedItem, String> {
+    let (message_encoding, signature_encoding) = message_encodings(
+        item.message_encoding.as_deref().or(request.message_encoding.as_deref()),
+        item.signature_encoding.as_deref().or(request.signature_encoding.as_deref()),
+    )?;
+
+    let (signature, pubkey) = decode_signature_and_pubkey(&item.signature, signature_encoding, &item.pubkey)?;
+    let message = message_encoding.decode(&item.message).ok_or("Invalid message encoding")?;
+
+    Ok(DecodedItem { index, message, signature, pubkey })
+}
+
+fn verify_one(item: &DecodedItem) -> bool {
+    item.signature.verify(item.pubkey.as_ref(), &item.message)
+}
+
+// Batch verification checks a whole chunk at once but only says whether every
+// signature in it holds, so a failing chunk is re-checked item by item.
+fn verify_chunk(chunk: &[DecodedItem]) -> Vec<(usize, bool)> {
+    let keys = chunk.iter()
+        .map(|item| ed25519_dalek::PublicKey::from_bytes(item.pubkey.as_ref()))
+        .collect::<Result<Vec<_>, _>>();
+    let signatures = chunk.iter()
+        .map(|item| ed25519_dalek::Signature::from_bytes(item.signature.as_ref()))
+        .collect::<Result<Vec<_>, _>>();
+
+    if let (Ok(keys), Ok(signatures)) = (keys, signatures) {
+        let messages = chunk.iter().map(|item| item.message.as_slice()).collect::<Vec<_>>();
+        if ed25519_dalek::verify_batch(&messages, &signatures, &keys).is_ok() {
+            return chunk.iter().map(|item| (item.index, true)).collect();
+        }
+    }
+
+    chunk.iter().map(|item| (item.index, verify_one(item))).collect()
+}
+
+// By default every item gets the same strict check as /message/verify.
+// `cofactored` opts into batch equations, which are faster but can accept
+// signatures with small-order components that the strict check rejects.
+fn verify_all(decoded: &[DecodedItem], cofactored: bool) -> Vec<(usize, bool)> {
+    if cofactored {
+        decoded.par_chunks(BATCH_CHUNK_SIZE).flat_map_iter(verify_chunk).collect()
+    } else {
+        decoded.par_iter().map(|item| (item.index, verify_one(item))).collect()
+    }
+}
+
+pub fn verify_batch(content: &str) -> JsonResponse {
+    let data: VerifyBatchRequest = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    if data.items.is_empty() || data.items.len() > MAX_BATCH_ITEMS {
+        return error_response(400, "Items must contain between 1 and 10000 entries");
+    }
+
+    // Request-level defaults apply to every item, so a bad one fails the request
+    if let Err(error) = message_encodings(data.message_encoding.as_deref(), data.signature_encoding.as_deref()) {
+        return error_response(400, &error);
+    }
+
+    let mut results = (0..data.items.len())
+        .map(|index| VerifyResult { index, valid: false, error: None })
+        .collect::<Vec<_>>();
+
+    let mut decoded = Vec::with_capacity(data.items.len());
+    for (index, item) in data.items.iter().enumerate() {
+        match decode_item(index, item, &data) {
+            Ok(item) => decoded.push(item),
+            Err(error) => results[index].error = Some(error),
+        }
+    }
+
+    for (index, valid) in verify_all(&decoded, data.cofactored) {
+        results[index].valid = valid;
+    }
+
+    let valid_count = results.iter().filter(|result| result.valid).count();
+
+    let response_data = VerifyBatchResponse {
+        success: true,
+        data: VerifyBatchData {
+            valid_count,
+            invalid_count: results.len() - valid_count,
+            results,
+        },
+    };
+
+    json_response(200, serde_json::to_string(&response_data).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use solana_sdk::{signature::Keypair, signer::Signer};
+
+    // The identity point as both pubkey and R with s = 0 satisfies the
+    // cofactored equation, but the pubkey has small order.
+    fn small_order_item(index: usize) -> DecodedItem {
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(&identity);
+        DecodedItem { index, message: b"hello".to_vec(), signature: Signature::from(signature), pubkey: Pubkey::from(identity) }
+    }
+
+    fn signed_item(index: usize) -> DecodedItem {
+        let keypair = Keypair::new();
+        let message = b"hello".to_vec();
+        DecodedItem { index, signature: keypair.sign_message(&message), pubkey: keypair.pubkey(), message }
+    }
+
+    #[test]
+    fn default_results_match_single_verify() {
+        let items = vec![signed_item(0), small_order_item(1), signed_item(2)];
+        for (index, valid) in verify_all(&items, false) {
+            assert_eq!(valid, verify_one(&items[index]));
+        }
+        assert!(!verify_all(&items, false)[1].1);
+    }
+
+    #[test]
+    fn cofactored_batch_is_opt_in() {
+        let items = vec![signed_item(0), small_order_item(1)];
+        assert_eq!(verify_all(&items, true), vec![(0, true), (1, true)]);
+    }
+
+    #[test]
+    fn unsupported_request_encoding_fails_the_request() {
+        let keypair = Keypair::new();
+        let item = json!({
+            "message": "hello",
+            "signature": bs58::encode(keypair.sign_message(b"hello")).into_string(),
+            "pubkey": keypair.pubkey().to_string(),
+        });
+        let content = json!({ "items": [item.clone(), item], "signatureEncoding": "utf8" }).to_string();
+
+        assert_eq!(verify_batch(&content).status_code().0, 400);
+    }
+}
@@ -0,0 +1,51 @@
+use std::str::FromStr;
+use base64::{engine::general_purpose, Engine};
+
+// Byte encodings accepted for messages and signatures
+#[derive(Clone, Copy, PartialEq)]
+pub enum Encoding {
+    Utf8,
+    Hex,
+    Base64,
+    Base58,
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "utf8" => Ok(Encoding::Utf8),
+            "hex" => Ok(Encoding::Hex),
+            "base64" => Ok(Encoding::Base64),
+            "base58" => Ok(Encoding::Base58),
+            other => Err(format!("Unsupported encoding: {}", other)),
+        }
+    }
+}
+
+impl Encoding {
+    // Parses an optional request field, falling back to `default` when absent
+    pub fn parse_or(value: Option<&str>, default: Encoding) -> Result<Encoding, String> {
+        value.map_or(Ok(default), Encoding::from_str)
+    }
+
+    pub fn decode(self, value: &str) -> Option<Vec<u8>> {
+        match self {
+            Encoding::Utf8 => Some(value.as_bytes().to_vec()),
+            Encoding::Hex => hex::decode(value).ok(),
+            Encoding::Base64 => general_purpose::STANDARD.decode(value).ok(),
+            Encoding::Base58 => bs58::decode(value).into_vec().ok(),
+        }
+    }
+
+    // Raw bytes such as signatures need a binary encoding
+    pub fn encode(self, bytes: &[u8]) -> Result<String, &'static str> {
+        match self {
+            Encoding::Utf8 => Err("utf8 cannot encode binary data; use base64, base58 or hex"),
+            Encoding::Base64 => Ok(general_purpose::STANDARD.encode(bytes)),
+            Encoding::Hex => Ok(hex::encode(bytes)),
+            Encoding::Base58 => Ok(bs58::encode(bytes).into_string()),
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::HashSet;
+use serde::{Serialize, Deserialize};
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_sdk::transaction::VersionedTransaction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::{
+    address_lookup_table, bpf_loader, bpf_loader_deprecated, bpf_loader_upgradeable, compute_budget, config,
+    ed25519_program, loader_v4, secp256k1_program, secp256r1_program, stake, system_program, vote,
+    zk_elgamal_proof_program, zk_token_proof_program,
+};
+
+use crate::encoding::Encoding;
+use crate::instruction_decode::parse_compute_budget;
+use crate::{error_response, json_response, parse_request, JsonResponse};
+
+const DEFAULT_LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u64 = 200_000;
+// What the runtime allots a builtin program's instruction when no limit is set
+const BUILTIN_INSTRUCTION_COMPUTE_UNIT_LIMIT: u64 = 3_000;
+const MAX_COMPUTE_UNIT_LIMIT: u64 = 1_400_000;
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+
+#[derive(Deserialize)]
+struct FeeEstimateRequest {
+    transaction: String,
+    #[serde(default)]
+    encoding: Option<String>,
+    #[serde(rename = "lamportsPerSignature", default)]
+    lamports_per_signature: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct FeeEstimateResponse {
+    success: bool,
+    data: FeeEstimate,
+}
+
+#[derive(Serialize)]
+struct FeeEstimate {
+    signatures: u64,
+    precompile_signatures: u64,
+    lamports_per_signature: u64,
+    base_fee: u64,
+    compute_unit_limit: u64,
+    compute_unit_price: u64,
+    priority_fee: u64,
+    total_fee: u64,
+}
+
+// Programs the runtime runs natively, precompiles included
+const BUILTIN_PROGRAMS: &[Pubkey] = &[
+    system_program::ID,
+    vote::ID,
+    stake::ID,
+    config::ID,
+    compute_budget::ID,
+    address_lookup_table::ID,
+    bpf_loader::ID,
+    bpf_loader_deprecated::ID,
+    bpf_loader_upgradeable::ID,
+    loader_v4::ID,
+    zk_token_proof_program::ID,
+    zk_elgamal_proof_program::ID,
+    ed25519_program::ID,
+    secp256k1_program::ID,
+    secp256r1_program::ID,
+];
+
+pub fn estimate(content: &str) -> JsonResponse {
+    match build_estimate(content) {
+        Ok(data) => json_response(200, serde_json::to_string(&FeeEstimateResponse { success: true, data }).unwrap()),
+        Err(error) => error_response(400, &error),
+    }
+}
+
+// The base fee covers every transaction signature plus those verified by the
+// signature precompiles; the priority fee is the compute unit price (in
+// micro-lamports) times the requested or default compute unit limit. The
+// default gives builtin instructions far less than program instructions.
+fn build_estimate(content: &str) -> Result<FeeEstimate, String> {
+    let data: FeeEstimateRequest = parse_request(content)?;
+    let encoding = Encoding::parse_or(data.encoding.as_deref(), Encoding::Base64)?;
+    let bytes = encoding.decode(&data.transaction).ok_or_else(|| "Invalid transaction encoding".to_string())?;
+    let transaction: VersionedTransaction = bincode::deserialize(&bytes).map_err(|_| "Invalid transaction".to_string())?;
+
+    let message = &transaction.message;
+    let account_keys = message.static_account_keys();
+
+    let mut precompile_signatures = 0;
+    let mut builtin_instructions = 0;
+    let mut program_instructions = 0;
+    let mut compute_unit_limit = None;
+    let mut compute_unit_price = None;
+    let mut seen = HashSet::new();
+    for (index, ix) in message.instructions().iter().enumerate() {
+        let program_id = account_keys.get(usize::from(ix.program_id_index))
+            .ok_or_else(|| format!("Instruction {} has an invalid program index", index))?;
+
+        if [ed25519_program::ID, secp256k1_program::ID, secp256r1_program::ID].contains(program_id) {
+            // The signature precompiles all lead their data with the signature count
+            precompile_signatures += u64::from(ix.data.first().copied().unwrap_or(0));
+        }
+
+        if BUILTIN_PROGRAMS.contains(program_id) {
+            builtin_instructions += 1;
+        } else {
+            program_instructions += 1;
+        }
+        if *program_id != compute_budget::ID {
+            continue;
+        }
+
+        // The runtime rejects a transaction that sets the same budget twice
+        let instruction = parse_compute_budget(&ix.data).map_err(|error| format!("Instruction {}: {}", index, error))?;
+        if !seen.insert(std::mem::discriminant(&instruction)) {
+            return Err(format!("Instruction {} duplicates an earlier compute budget instruction", index));
+        }
+        match instruction {
+            ComputeBudgetInstruction::SetComputeUnitLimit(units) => compute_unit_limit = Some(u64::from(units)),
+            ComputeBudgetInstruction::SetComputeUnitPrice(micro_lamports) => compute_unit_price = Some(micro_lamports),
+            ComputeBudgetInstruction::RequestHeapFrame(_) | ComputeBudgetInstruction::SetLoadedAccountsDataSizeLimit(_) => {}
+            ComputeBudgetInstruction::Unused => {
+                return Err(format!("Instruction {}: Invalid compute budget instruction data", index));
+            }
+        }
+    }
+
+    let compute_unit_limit = compute_unit_limit
+        .unwrap_or(program_instructions * DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT + builtin_instructions * BUILTIN_INSTRUCTION_COMPUTE_UNIT_LIMIT)
+        .min(MAX_COMPUTE_UNIT_LIMIT);
+    let compute_unit_price = compute_unit_price.unwrap_or(0);
+    let priority_fee = (u128::from(compute_unit_price) * u128::from(compute_unit_limit)).div_ceil(MICRO_LAMPORTS_PER_LAMPORT);
+    let priority_fee = u64::try_from(priority_fee).map_err(|_| "Priority fee overflows".to_string())?;
+
+    let signatures = u64::from(message.header().num_required_signatures);
+    let lamports_per_signature = data.lamports_per_signature.unwrap_or(DEFAULT_LAMPORTS_PER_SIGNATURE);
+    let base_fee = (signatures + precompile_signatures).saturating_mul(lamports_per_signature);
+
+    Ok(FeeEstimate {
+        signatures,
+        precompile_signatures,
+        lamports_per_signature,
+        base_fee,
+        compute_unit_limit,
+        compute_unit_price,
+        priority_fee,
+        total_fee: base_fee.saturating_add(priority_fee),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{instruction::Instruction, message::Message, transaction::Transaction};
+    use solana_system_interface::instruction as system_instruction;
+
+    fn estimate_for(instructions: &[Instruction]) -> Result<FeeEstimate, String> {
+        let payer = Pubkey::new_unique();
+        let transaction = Transaction::new_unsigned(Message::new(instructions, Some(&payer)));
+        let encoded = Encoding::Base64.encode(&bincode::serialize(&transaction).unwrap()).unwrap();
+        build_estimate(&serde_json::json!({ "transaction": encoded }).to_string())
+    }
+
+    #[test]
+    fn builtin_instructions_get_the_builtin_default_limit() {
+        let payer = Pubkey::new_unique();
+        let estimate = estimate_for(&[
+            ComputeBudgetInstruction::set_compute_unit_price(1_000_000),
+            system_instruction::transfer(&payer, &Pubkey::new_unique(), 1),
+        ]).unwrap();
+        assert_eq!(estimate.compute_unit_limit, 2 * BUILTIN_INSTRUCTION_COMPUTE_UNIT_LIMIT);
+        assert_eq!(estimate.priority_fee, 2 * BUILTIN_INSTRUCTION_COMPUTE_UNIT_LIMIT);
+    }
+
+    #[test]
+    fn program_instructions_get_the_program_default_limit() {
+        let memo = Instruction::new_with_bytes(crate::memo::MEMO_PROGRAM_ID, b"hi", vec![]);
+        let estimate = estimate_for(&[memo]).unwrap();
+        assert_eq!(estimate.compute_unit_limit, DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT);
+    }
+
+    #[test]
+    fn unused_compute_budget_instruction_is_rejected() {
+        let unused = Instruction::new_with_bytes(compute_budget::ID, &[0], vec![]);
+        assert!(estimate_for(&[unused]).is_err());
+    }
+
+    #[test]
+    fn precompile_signatures_are_counted() {
+        let estimate = estimate_for(&[
+            Instruction::new_with_bytes(ed25519_program::ID, &[1, 0], vec![]),
+            Instruction::new_with_bytes(secp256k1_program::ID, &[2], vec![]),
+            Instruction::new_with_bytes(secp256r1_program::ID, &[3, 0], vec![]),
+        ]).unwrap();
+        assert_eq!(estimate.precompile_signatures, 6);
+    }
+}
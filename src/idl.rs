@@ -0,0 +1,625 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use serde::{Serialize, Deserialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use solana_sdk::{instruction::{AccountMeta, Instruction}, pubkey::Pubkey};
+
+use crate::encoding::Encoding;
+use crate::{error_response, json_response, parse_pubkey, JsonResponse, TokenData};
+
+// Registered Anchor programs keyed by program id. Both the 0.30+ IDL layout
+// and the older one (camelCase names, `isMut`/`isSigner`, no discriminators)
+// are normalised into the same shape when loaded.
+#[derive(Default)]
+pub struct IdlRegistry {
+    programs: HashMap<Pubkey, Program>,
+}
+
+struct Program {
+    name: String,
+    instructions: Vec<IdlInstruction>,
+    types: HashMap<String, Value>,
+}
+
+struct IdlInstruction {
+    name: String,
+    discriminator: Vec<u8>,
+    accounts: Vec<IdlAccount>,
+    args: Vec<(String, Value)>,
+}
+
+enum IdlAccount {
+    Single {
+        name: String,
+        writable: bool,
+        signer: bool,
+        optional: bool,
+        address: Option<Pubkey>,
+    },
+    Group {
+        name: String,
+        accounts: Vec<IdlAccount>,
+    },
+}
+
+#[derive(Deserialize)]
+struct BuildRequest {
+    #[serde(default)]
+    args: Map<String, Value>,
+    #[serde(default)]
+    accounts: Map<String, Value>,
+    #[serde(rename = "remainingAccounts", default)]
+    remaining_accounts: Vec<RemainingAccount>,
+}
+
+#[derive(Deserialize)]
+struct RemainingAccount {
+    pubkey: String,
+    #[serde(rename = "isSigner", default)]
+    is_signer: bool,
+    #[serde(rename = "isWritable", default)]
+    is_writable: bool,
+}
+
+#[derive(Serialize)]
+struct BuildResponse {
+    success: bool,
+    data: TokenData,
+}
+
+#[derive(Serialize)]
+struct ProgramResponse {
+    success: bool,
+    data: ProgramSummary,
+}
+
+#[derive(Serialize)]
+struct ProgramListResponse {
+    success: bool,
+    data: Vec<ProgramSummary>,
+}
+
+#[derive(Serialize)]
+struct ProgramSummary {
+    program_id: String,
+    name: String,
+    instructions: Vec<String>,
+}
+
+// Anchor derives legacy names with heck's snake case, which keeps acronyms
+// together ("setURI" -> "set_uri").
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::with_capacity(name.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_is_lower) {
+                out.push('_');
+            }
+        }
+        out.extend(c.to_lowercase());
+    }
+    out
+}
+
+fn sighash(name: &str) -> Vec<u8> {
+    Sha256::digest(format!("global:{}", snake_case(name)).as_bytes())[..8].to_vec()
+}
+
+fn parse_accounts(accounts: &[Value]) -> Result<Vec<IdlAccount>, String> {
+    accounts.iter().map(|account| {
+        let name = account["name"].as_str().ok_or("Account without a name")?.to_string();
+        if let Some(nested) = account["accounts"].as_array() {
+            return Ok(IdlAccount::Group { name, accounts: parse_accounts(nested)? });
+        }
+
+        let flag = |new: &str, old: &str| account[new].as_bool().or(account[old].as_bool()).unwrap_or(false);
+        let address = match account["address"].as_str() {
+            Some(address) => Some(Pubkey::from_str(address).map_err(|_| format!("Account {} has an invalid address", name))?),
+            None => None,
+        };
+
+        Ok(IdlAccount::Single {
+            writable: flag("writable", "isMut"),
+            signer: flag("signer", "isSigner"),
+            optional: flag("optional", "isOptional"),
+            address,
+            name,
+        })
+    }).collect()
+}
+
+// The defined type an alias resolves to, looking through option wrappers
+fn alias_target(ty: &Value) -> Option<&str> {
+    if let Some(inner) = ty.get("option").or_else(|| ty.get("coption")) {
+        return alias_target(inner);
+    }
+    let defined = ty.get("defined")?;
+    defined.as_str().or(defined["name"].as_str())
+}
+
+// An alias chain that comes back to a type it started from never bottoms out
+fn check_aliases(types: &HashMap<String, Value>) -> Result<(), String> {
+    for start in types.keys() {
+        let mut seen = vec![start.as_str()];
+        let mut current = start.as_str();
+        while let Some(definition) = types.get(current).filter(|definition| definition["kind"] == "type") {
+            let Some(next) = alias_target(&definition["alias"]) else { break };
+            if seen.contains(&next) {
+                return Err(format!("Type {} has a cyclic alias", start));
+            }
+            seen.push(next);
+            current = next;
+        }
+    }
+    Ok(())
+}
+
+impl Program {
+    fn parse(idl: &Value, address_override: Option<Pubkey>) -> Result<(Pubkey, Program), String> {
+        let address = match address_override {
+            Some(address) => address,
+            None => {
+                let address = idl["address"].as_str().or(idl["metadata"]["address"].as_str())
+                    .ok_or("IDL has no program address; pass ?address=")?;
+                Pubkey::from_str(address).map_err(|_| "IDL program address is invalid")?
+            }
+        };
+
+        let name = idl["metadata"]["name"].as_str().or(idl["name"].as_str()).unwrap_or_default().to_string();
+
+        let mut instructions = Vec::new();
+        for ix in idl["instructions"].as_array().ok_or("IDL has no instructions")? {
+            let ix_name = ix["name"].as_str().ok_or("Instruction without a name")?.to_string();
+
+            let discriminator = match ix["discriminator"].as_array() {
+                Some(bytes) => bytes.iter()
+                    .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+                    .collect::<Option<Vec<u8>>>()
+                    .ok_or_else(|| format!("Instruction {} has an invalid discriminator", ix_name))?,
+                None => sighash(&ix_name),
+            };
+
+            let accounts = parse_accounts(ix["accounts"].as_array().map(Vec::as_slice).unwrap_or_default())?;
+
+            let args = ix["args"].as_array().map(Vec::as_slice).unwrap_or_default().iter().map(|arg| {
+                let arg_name = arg["name"].as_str().ok_or_else(|| format!("Instruction {} has an unnamed arg", ix_name))?;
+                Ok((arg_name.to_string(), arg["type"].clone()))
+            }).collect::<Result<Vec<_>, String>>()?;
+
+            instructions.push(IdlInstruction { name: ix_name, discriminator, accounts, args });
+        }
+
+        let types = idl["types"].as_array().map(Vec::as_slice).unwrap_or_default().iter()
+            .filter_map(|ty| Some((ty["name"].as_str()?.to_string(), ty["type"].clone())))
+            .collect();
+        check_aliases(&types)?;
+
+        Ok((address, Program { name, instructions, types }))
+    }
+
+    fn instruction(&self, name: &str) -> Option<&IdlInstruction> {
+        let wanted = snake_case(name);
+        self.instructions.iter().find(|ix| ix.name == name || snake_case(&ix.name) == wanted)
+    }
+}
+
+impl IdlRegistry {
+    // Loads every `.json` file in `dir` as an IDL
+    pub fn load_dir(dir: &Path) -> Result<IdlRegistry, String> {
+        let mut registry = IdlRegistry::default();
+        let entries = fs::read_dir(dir).map_err(|err| format!("Failed to read IDL directory: {}", err))?;
+
+        for entry in entries {
+            let path = entry.map_err(|err| format!("Failed to read IDL directory: {}", err))?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+
+            let contents = fs::read_to_string(&path).map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+            let idl: Value = serde_json::from_str(&contents).map_err(|_| format!("{} is not valid JSON", path.display()))?;
+            let (address, program) = Program::parse(&idl, None).map_err(|err| format!("{}: {}", path.display(), err))?;
+            registry.programs.insert(address, program);
+        }
+
+        Ok(registry)
+    }
+
+    // `{program}` in a route may be the program id or the IDL's name
+    fn find(&self, program: &str) -> Option<(&Pubkey, &Program)> {
+        if let Ok(address) = Pubkey::from_str(program) {
+            if let Some(found) = self.programs.get_key_value(&address) {
+                return Some(found);
+            }
+        }
+        self.programs.iter().find(|(_, p)| p.name == program)
+    }
+}
+
+fn field<'a>(object: &'a Map<String, Value>, name: &str) -> Option<&'a Value> {
+    object.get(name).or_else(|| object.get(&snake_case(name))).or_else(|| {
+        let camel = name.split('_').enumerate().map(|(i, part)| {
+            let mut chars = part.chars();
+            match (i, chars.next()) {
+                (0, Some(first)) => first.to_string() + chars.as_str(),
+                (_, Some(first)) => first.to_uppercase().collect::<String>() + chars.as_str(),
+                (_, None) => String::new(),
+            }
+        }).collect::<String>();
+        object.get(&camel)
+    })
+}
+
+fn integer<T: TryFrom<i128>>(value: &Value, ty: &str, path: &str) -> Result<T, String> {
+    let parsed = match value {
+        Value::Number(n) => n.as_i64().map(i128::from).or_else(|| n.as_u64().map(i128::from)),
+        Value::String(s) => s.parse::<i128>().ok(),
+        _ => None,
+    };
+    parsed.and_then(|n| T::try_from(n).ok()).ok_or_else(|| format!("{}: expected {}", path, ty))
+}
+
+// u128 values above i128::MAX can only arrive as strings
+fn unsigned_128(value: &Value, path: &str) -> Result<u128, String> {
+    match value {
+        Value::Number(n) => n.as_u64().map(u128::from),
+        Value::String(s) => s.parse::<u128>().ok(),
+        _ => None,
+    }.ok_or_else(|| format!("{}: expected u128", path))
+}
+
+// Defined types nest through option, coption and alias without consuming any
+// of the value, so expansions are counted to stop a type that loops back on itself.
+const MAX_DEFINED_DEPTH: usize = 32;
+
+struct Encoder<'a> {
+    types: &'a HashMap<String, Value>,
+    out: Vec<u8>,
+    depth: usize,
+}
+
+impl Encoder<'_> {
+    fn encode(&mut self, ty: &Value, value: &Value, path: &str) -> Result<(), String> {
+        match ty {
+            Value::String(primitive) => self.primitive(primitive, value, path),
+            Value::Object(ty) => {
+                if let Some(inner) = ty.get("option") {
+                    if value.is_null() {
+                        self.out.push(0);
+                        Ok(())
+                    } else {
+                        self.out.push(1);
+                        self.encode(inner, value, path)
+                    }
+                } else if let Some(inner) = ty.get("coption") {
+                    if value.is_null() {
+                        self.out.extend_from_slice(&0u32.to_le_bytes());
+                        Ok(())
+                    } else {
+                        self.out.extend_from_slice(&1u32.to_le_bytes());
+                        self.encode(inner, value, path)
+                    }
+                } else if let Some(inner) = ty.get("vec") {
+                    let items = value.as_array().ok_or_else(|| format!("{}: expected an array", path))?;
+                    self.out.extend_from_slice(&(items.len() as u32).to_le_bytes());
+                    for (i, item) in items.iter().enumerate() {
+                        self.encode(inner, item, &format!("{}[{}]", path, i))?;
+                    }
+                    Ok(())
+                } else if let Some(array) = ty.get("array") {
+                    let (inner, len) = match array.as_array().map(Vec::as_slice) {
+                        Some([inner, len]) => (inner, len.as_u64().ok_or_else(|| format!("{}: array length must be a number", path))?),
+                        _ => return Err(format!("{}: malformed array type", path)),
+                    };
+                    let items = value.as_array().ok_or_else(|| format!("{}: expected an array", path))?;
+                    if items.len() as u64 != len {
+                        return Err(format!("{}: expected {} items, got {}", path, len, items.len()));
+                    }
+                    for (i, item) in items.iter().enumerate() {
+                        self.encode(inner, item, &format!("{}[{}]", path, i))?;
+                    }
+                    Ok(())
+                } else if let Some(defined) = ty.get("defined") {
+                    let name = defined.as_str().or(defined["name"].as_str())
+                        .ok_or_else(|| format!("{}: malformed defined type", path))?;
+                    if defined.get("generics").is_some_and(|g| g.as_array().is_some_and(|g| !g.is_empty())) {
+                        return Err(format!("{}: generic type {} is not supported", path, name));
+                    }
+                    let definition = self.types.get(name).ok_or_else(|| format!("{}: unknown type {}", path, name))?;
+                    if self.depth == MAX_DEFINED_DEPTH {
+                        return Err(format!("{}: type {} nests too deeply", path, name));
+                    }
+                    self.depth += 1;
+                    let result = self.defined(definition, value, path);
+                    self.depth -= 1;
+                    result
+                } else {
+                    Err(format!("{}: unsupported type {}", path, Value::Object(ty.clone())))
+                }
+            }
+            other => Err(format!("{}: unsupported type {}", path, other)),
+        }
+    }
+
+    fn primitive(&mut self, ty: &str, value: &Value, path: &str) -> Result<(), String> {
+        match ty {
+            "bool" => self.out.push(value.as_bool().ok_or_else(|| format!("{}: expected a boolean", path))? as u8),
+            "u8" => self.out.push(integer::<u8>(value, ty, path)?),
+            "i8" => self.out.extend_from_slice(&integer::<i8>(value, ty, path)?.to_le_bytes()),
+            "u16" => self.out.extend_from_slice(&integer::<u16>(value, ty, path)?.to_le_bytes()),
+            "i16" => self.out.extend_from_slice(&integer::<i16>(value, ty, path)?.to_le_bytes()),
+            "u32" => self.out.extend_from_slice(&integer::<u32>(value, ty, path)?.to_le_bytes()),
+            "i32" => self.out.extend_from_slice(&integer::<i32>(value, ty, path)?.to_le_bytes()),
+            "u64" => self.out.extend_from_slice(&integer::<u64>(value, ty, path)?.to_le_bytes()),
+            "i64" => self.out.extend_from_slice(&integer::<i64>(value, ty, path)?.to_le_bytes()),
+            "u128" => self.out.extend_from_slice(&unsigned_128(value, path)?.to_le_bytes()),
+            "i128" => self.out.extend_from_slice(&integer::<i128>(value, ty, path)?.to_le_bytes()),
+            "f32" => {
+                let n = value.as_f64().ok_or_else(|| format!("{}: expected a number", path))?;
+                self.out.extend_from_slice(&(n as f32).to_le_bytes());
+            }
+            "f64" => {
+                let n = value.as_f64().ok_or_else(|| format!("{}: expected a number", path))?;
+                self.out.extend_from_slice(&n.to_le_bytes());
+            }
+            "string" => {
+                let s = value.as_str().ok_or_else(|| format!("{}: expected a string", path))?;
+                self.out.extend_from_slice(&(s.len() as u32).to_le_bytes());
+                self.out.extend_from_slice(s.as_bytes());
+            }
+            // Bytes come as an array of numbers or a base64 string
+            "bytes" => {
+                let bytes = match value {
+                    Value::Array(items) => items.iter()
+                        .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+                        .collect::<Option<Vec<u8>>>(),
+                    Value::String(s) => Encoding::Base64.decode(s),
+                    _ => None,
+                }.ok_or_else(|| format!("{}: expected bytes as an array or base64 string", path))?;
+                self.out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+                self.out.extend_from_slice(&bytes);
+            }
+            "pubkey" | "publicKey" => {
+                let s = value.as_str().ok_or_else(|| format!("{}: expected a base58 address", path))?;
+                let pubkey = Pubkey::from_str(s).map_err(|_| format!("{}: invalid address", path))?;
+                self.out.extend_from_slice(pubkey.as_ref());
+            }
+            other => return Err(format!("{}: unsupported type {}", path, other)),
+        }
+        Ok(())
+    }
+
+    fn defined(&mut self, definition: &Value, value: &Value, path: &str) -> Result<(), String> {
+        match definition["kind"].as_str() {
+            Some("struct") => self.fields(&definition["fields"], value, path),
+            Some("enum") => {
+                let variants = definition["variants"].as_array().ok_or_else(|| format!("{}: enum without variants", path))?;
+
+                // Unit variants may be given by name alone; others as { "Variant": fields }
+                let (variant_name, fields_value) = match value {
+                    Value::String(name) => (name.as_str(), &Value::Null),
+                    Value::Object(object) if object.len() == 1 => {
+                        let (name, fields) = object.iter().next().unwrap();
+                        (name.as_str(), fields)
+                    }
+                    _ => return Err(format!("{}: expected an enum variant", path)),
+                };
+
+                let index = variants.iter()
+                    .position(|variant| variant["name"].as_str().is_some_and(|name| name.eq_ignore_ascii_case(variant_name)))
+                    .ok_or_else(|| format!("{}: unknown variant {}", path, variant_name))?;
+                // Borsh writes the variant index as a single byte
+                let discriminant = u8::try_from(index).map_err(|_| format!("{}: Enum has too many variants", path))?;
+                self.out.push(discriminant);
+                self.fields(&variants[index]["fields"], fields_value, &format!("{}.{}", path, variant_name))
+            }
+            Some("type") => self.encode(&definition["alias"], value, path),
+            _ => Err(format!("{}: unsupported type definition", path)),
+        }
+    }
+
+    // Named fields are read from an object, tuple fields from an array
+    fn fields(&mut self, fields: &Value, value: &Value, path: &str) -> Result<(), String> {
+        let fields = match fields.as_array() {
+            Some(fields) if !fields.is_empty() => fields,
+            _ => return Ok(()),
+        };
+
+        if fields[0].get("name").is_some() {
+            let object = value.as_object().ok_or_else(|| format!("{}: expected an object", path))?;
+            for f in fields {
+                let name = f["name"].as_str().unwrap_or_default();
+                let field_path = format!("{}.{}", path, name);
+                let field_value = field(object, name).ok_or_else(|| format!("{}: missing", field_path))?;
+                self.encode(&f["type"], field_value, &field_path)?;
+            }
+        } else {
+            let items = value.as_array().ok_or_else(|| format!("{}: expected an array", path))?;
+            if items.len() != fields.len() {
+                return Err(format!("{}: expected {} items, got {}", path, fields.len(), items.len()));
+            }
+            for (i, (ty, item)) in fields.iter().zip(items).enumerate() {
+                self.encode(ty, item, &format!("{}[{}]", path, i))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Resolves IDL accounts in order. Groups take a nested object; optional
+// accounts that are left out are replaced by the program id, as Anchor does.
+fn resolve_accounts(
+    accounts: &[IdlAccount],
+    given: &Map<String, Value>,
+    program_id: &Pubkey,
+    path: &str,
+    metas: &mut Vec<AccountMeta>,
+) -> Result<(), String> {
+    for account in accounts {
+        match account {
+            IdlAccount::Group { name, accounts } => {
+                let nested = field(given, name).and_then(Value::as_object)
+                    .ok_or_else(|| format!("{}{}: expected an object of accounts", path, name))?;
+                resolve_accounts(accounts, nested, program_id, &format!("{}{}.", path, name), metas)?;
+            }
+            IdlAccount::Single { name, writable, signer, optional, address } => {
+                let pubkey = match (field(given, name).and_then(Value::as_str), address, optional) {
+                    (Some(value), _, _) => parse_pubkey(value, &format!("{}{}", path, name))?,
+                    (None, Some(address), _) => *address,
+                    (None, None, true) => {
+                        metas.push(AccountMeta::new_readonly(*program_id, false));
+                        continue;
+                    }
+                    (None, None, false) => return Err(format!("Missing account {}{}", path, name)),
+                };
+                metas.push(AccountMeta { pubkey, is_signer: *signer, is_writable: *writable });
+            }
+        }
+    }
+    Ok(())
+}
+
+fn build(program_id: &Pubkey, program: &Program, ix: &IdlInstruction, data: BuildRequest) -> Result<Instruction, String> {
+    let mut metas = Vec::new();
+    resolve_accounts(&ix.accounts, &data.accounts, program_id, "", &mut metas)?;
+    for (i, account) in data.remaining_accounts.iter().enumerate() {
+        let pubkey = parse_pubkey(&account.pubkey, &format!("remainingAccounts[{}]", i))?;
+        metas.push(AccountMeta { pubkey, is_signer: account.is_signer, is_writable: account.is_writable });
+    }
+
+    let mut encoder = Encoder { types: &program.types, out: ix.discriminator.clone(), depth: 0 };
+    for (name, ty) in &ix.args {
+        let value = field(&data.args, name).unwrap_or(&Value::Null);
+        // Only an option may be left out
+        if value.is_null() && ty.get("option").is_none() && ty.get("coption").is_none() {
+            return Err(format!("Missing arg {}", name));
+        }
+        encoder.encode(ty, value, name)?;
+    }
+
+    Ok(Instruction { program_id: *program_id, accounts: metas, data: encoder.out })
+}
+
+pub fn build_instruction(registry: &IdlRegistry, program: &str, instruction: &str, content: &str) -> JsonResponse {
+    let (program_id, program) = match registry.find(program) {
+        Some(found) => found,
+        None => return error_response(404, "Unknown program; register its IDL first"),
+    };
+
+    let ix = match program.instruction(instruction) {
+        Some(ix) => ix,
+        None => return error_response(404, &format!("Program {} has no instruction {}", program.name, instruction)),
+    };
+
+    let data: BuildRequest = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    match build(program_id, program, ix, data) {
+        Ok(ix) => json_response(200, serde_json::to_string(&BuildResponse { success: true, data: ix.into() }).unwrap()),
+        Err(error) => error_response(400, &error),
+    }
+}
+
+pub fn upload(registry: &mut IdlRegistry, query: &str, content: &str) -> JsonResponse {
+    let idl: Value = match serde_json::from_str(content) {
+        Ok(idl) => idl,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    let address_override = match crate::query_param(query, "address").map(Pubkey::from_str) {
+        Some(Ok(address)) => Some(address),
+        Some(Err(_)) => return error_response(400, "Invalid address"),
+        None => None,
+    };
+
+    let (address, program) = match Program::parse(&idl, address_override) {
+        Ok(parsed) => parsed,
+        Err(error) => return error_response(400, &format!("Invalid IDL: {}", error)),
+    };
+
+    let summary = summarize(&address, &program);
+    registry.programs.insert(address, program);
+
+    json_response(200, serde_json::to_string(&ProgramResponse { success: true, data: summary }).unwrap())
+}
+
+pub fn list(registry: &IdlRegistry) -> JsonResponse {
+    let mut programs: Vec<_> = registry.programs.iter().map(|(address, program)| summarize(address, program)).collect();
+    programs.sort_by(|a, b| a.name.cmp(&b.name));
+    json_response(200, serde_json::to_string(&ProgramListResponse { success: true, data: programs }).unwrap())
+}
+
+fn summarize(address: &Pubkey, program: &Program) -> ProgramSummary {
+    ProgramSummary {
+        program_id: address.to_string(),
+        name: program.name.clone(),
+        instructions: program.instructions.iter().map(|ix| ix.name.clone()).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn idl_with_types(types: Value) -> Value {
+        json!({
+            "address": Pubkey::new_unique().to_string(),
+            "metadata": { "name": "cyclic" },
+            "instructions": [{ "name": "run", "accounts": [], "args": [{ "name": "a", "type": { "defined": { "name": "A" } } }] }],
+            "types": types,
+        })
+    }
+
+    #[test]
+    fn self_referential_alias_is_rejected() {
+        let idl = idl_with_types(json!([{ "name": "A", "type": { "kind": "type", "alias": { "defined": "A" } } }]));
+        assert!(Program::parse(&idl, None).is_err());
+    }
+
+    #[test]
+    fn alias_cycle_through_option_is_rejected() {
+        let idl = idl_with_types(json!([
+            { "name": "A", "type": { "kind": "type", "alias": { "option": { "defined": "B" } } } },
+            { "name": "B", "type": { "kind": "type", "alias": { "defined": { "name": "A" } } } },
+        ]));
+        assert!(Program::parse(&idl, None).is_err());
+    }
+
+    #[test]
+    fn encoder_stops_expanding_a_cyclic_type() {
+        let types = HashMap::from([("A".to_string(), json!({ "kind": "type", "alias": { "defined": "A" } }))]);
+        let mut encoder = Encoder { types: &types, out: Vec::new(), depth: 0 };
+        assert!(encoder.encode(&json!({ "defined": "A" }), &json!(1), "a").is_err());
+    }
+
+    #[test]
+    fn acyclic_alias_encodes() {
+        let idl = idl_with_types(json!([{ "name": "A", "type": { "kind": "type", "alias": "u16" } }]));
+        let (address, program) = Program::parse(&idl, None).unwrap();
+        let request = BuildRequest {
+            args: json!({ "a": 258 }).as_object().unwrap().clone(),
+            accounts: Map::new(),
+            remaining_accounts: Vec::new(),
+        };
+        let ix = build(&address, &program, &program.instructions[0], request).unwrap();
+        assert_eq!(ix.data[8..], [2, 1]);
+    }
+
+    #[test]
+    fn enum_variant_past_255_is_rejected() {
+        let variants: Vec<Value> = (0..=256).map(|index| json!({ "name": format!("V{}", index) })).collect();
+        let types = HashMap::from([("E".to_string(), json!({ "kind": "enum", "variants": variants }))]);
+        let mut encoder = Encoder { types: &types, out: Vec::new(), depth: 0 };
+        assert!(encoder.encode(&json!({ "defined": "E" }), &json!("V255"), "e").is_ok());
+        assert_eq!(encoder.out, [255]);
+        assert!(encoder.encode(&json!({ "defined": "E" }), &json!("V256"), "e").is_err());
+    }
+}
@@ -0,0 +1,528 @@
+use serde::{Serialize, Deserialize};
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use solana_address_lookup_table_interface::instruction::ProgramInstruction as LookupTableInstruction;
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_loader_v3_interface::instruction::UpgradeableLoaderInstruction;
+use solana_sdk_ids::{address_lookup_table, bpf_loader_upgradeable, compute_budget, stake, system_program};
+use solana_stake_interface::instruction::StakeInstruction;
+use solana_system_interface::instruction::SystemInstruction;
+use spl_token_2022::extension::ExtensionType;
+use spl_token_2022::instruction::TokenInstruction;
+
+use crate::account_decode::{coption_address, extension_name};
+use crate::encoding::Encoding;
+use crate::{error_response, json_response, parse_pubkey, parse_request, JsonResponse, ASSOCIATED_TOKEN_PROGRAM_ID};
+
+// The original token program stops at UiAmountToAmount; everything after is
+// Token-2022 only.
+const LAST_LEGACY_TOKEN_INSTRUCTION: u8 = 24;
+
+#[derive(Deserialize)]
+struct DecodeInstructionRequest {
+    #[serde(rename = "programId")]
+    program_id: String,
+    #[serde(default)]
+    accounts: Vec<AccountKey>,
+    #[serde(default)]
+    data: String,
+    #[serde(rename = "dataEncoding", default)]
+    data_encoding: Option<String>,
+}
+
+// Accounts can be plain addresses or the account objects other routes return
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AccountKey {
+    Address(String),
+    Meta { pubkey: String },
+}
+
+#[derive(Serialize)]
+struct DecodeInstructionResponse {
+    success: bool,
+    data: DecodedInstructionData,
+}
+
+#[derive(Serialize)]
+struct DecodedInstructionData {
+    program: &'static str,
+    instruction: &'static str,
+    args: Value,
+    accounts: Vec<LabelledAccount>,
+}
+
+#[derive(Serialize)]
+struct LabelledAccount {
+    role: String,
+    pubkey: String,
+}
+
+// What a program's instruction data decodes to, plus the order its accounts
+// are expected in: required roles first, then optional ones, then any number
+// of `rest` accounts (multisig signers, memo signers).
+struct Decoded {
+    program: &'static str,
+    instruction: &'static str,
+    args: Value,
+    roles: &'static [&'static str],
+    optional: &'static [&'static str],
+    rest: Option<&'static str>,
+}
+
+impl Decoded {
+    fn new(program: &'static str, instruction: &'static str, args: Value, roles: &'static [&'static str]) -> Self {
+        Decoded { program, instruction, args, roles, optional: &[], rest: None }
+    }
+
+    fn optional(self, optional: &'static [&'static str]) -> Self {
+        Decoded { optional, ..self }
+    }
+
+    fn rest(self, rest: &'static str) -> Self {
+        Decoded { rest: Some(rest), ..self }
+    }
+
+    // Token instructions take the multisig's signers after the authority
+    fn signers(self) -> Self {
+        self.rest("signer")
+    }
+
+    fn label(&self, accounts: &[String]) -> Result<Vec<LabelledAccount>, String> {
+        if accounts.len() < self.roles.len() {
+            return Err(format!("{} expects at least {} accounts", self.instruction, self.roles.len()));
+        }
+
+        let known = self.roles.iter().chain(self.optional).count();
+        if self.rest.is_none() && accounts.len() > known {
+            return Err(format!("{} expects at most {} accounts", self.instruction, known));
+        }
+
+        Ok(accounts.iter().enumerate().map(|(index, pubkey)| {
+            let role = self.roles.iter().chain(self.optional).nth(index).copied().or(self.rest).unwrap_or_default();
+            LabelledAccount { role: role.to_string(), pubkey: pubkey.clone() }
+        }).collect())
+    }
+}
+
+pub fn decode(content: &str) -> JsonResponse {
+    match decode_request(content) {
+        Ok(data) => json_response(200, serde_json::to_string(&DecodeInstructionResponse { success: true, data }).unwrap()),
+        Err(error) => error_response(400, &error),
+    }
+}
+
+fn decode_request(content: &str) -> Result<DecodedInstructionData, String> {
+    let data: DecodeInstructionRequest = parse_request(content)?;
+    let program_id = parse_pubkey(&data.program_id, "program id")?;
+
+    let mut accounts = Vec::with_capacity(data.accounts.len());
+    for account in &data.accounts {
+        let pubkey = match account {
+            AccountKey::Address(pubkey) | AccountKey::Meta { pubkey } => pubkey,
+        };
+        accounts.push(parse_pubkey(pubkey, "account")?.to_string());
+    }
+
+    let encoding = Encoding::parse_or(data.data_encoding.as_deref(), Encoding::Base64)?;
+    let bytes = encoding.decode(&data.data).ok_or_else(|| "Invalid data encoding".to_string())?;
+
+    let decoded = if program_id == system_program::ID {
+        decode_system(&bytes)
+    } else if program_id == spl_token::ID {
+        match bytes.first() {
+            Some(&tag) if tag > LAST_LEGACY_TOKEN_INSTRUCTION => Err("Instruction is only supported by Token-2022".to_string()),
+            _ => decode_token("spl-token", &bytes),
+        }
+    } else if program_id == spl_token_2022::ID {
+        decode_token("spl-token-2022", &bytes)
+    } else if program_id == ASSOCIATED_TOKEN_PROGRAM_ID {
+        decode_associated_token(&bytes)
+    } else if program_id == crate::memo::MEMO_PROGRAM_ID {
+        decode_memo(&bytes)
+    } else if program_id == compute_budget::ID {
+        decode_compute_budget(&bytes)
+    } else if program_id == stake::ID {
+        decode_stake(&bytes)
+    } else if program_id == address_lookup_table::ID {
+        decode_lookup_table(&bytes)
+    } else if program_id == bpf_loader_upgradeable::ID {
+        decode_upgradeable_loader(&bytes)
+    } else {
+        Err("Unsupported program".to_string())
+    }?;
+
+    Ok(DecodedInstructionData {
+        program: decoded.program,
+        instruction: decoded.instruction,
+        accounts: decoded.label(&accounts)?,
+        args: decoded.args,
+    })
+}
+
+fn decode_system(bytes: &[u8]) -> Result<Decoded, String> {
+    let instruction: SystemInstruction = bincode::deserialize(bytes).map_err(|_| "Invalid system instruction data".to_string())?;
+    let program = "system";
+
+    Ok(match instruction {
+        SystemInstruction::CreateAccount { lamports, space, owner } => Decoded::new(program, "CreateAccount",
+            json!({ "lamports": lamports, "space": space, "owner": owner.to_string() }),
+            &["funding", "new_account"]),
+        SystemInstruction::Assign { owner } => Decoded::new(program, "Assign",
+            json!({ "owner": owner.to_string() }),
+            &["account"]),
+        SystemInstruction::Transfer { lamports } => Decoded::new(program, "Transfer",
+            json!({ "lamports": lamports }),
+            &["source", "destination"]),
+        SystemInstruction::CreateAccountWithSeed { base, seed, lamports, space, owner } => Decoded::new(program, "CreateAccountWithSeed",
+            json!({ "base": base.to_string(), "seed": seed, "lamports": lamports, "space": space, "owner": owner.to_string() }),
+            &["funding", "new_account"]).optional(&["base"]),
+        SystemInstruction::AdvanceNonceAccount => Decoded::new(program, "AdvanceNonceAccount",
+            json!({}),
+            &["nonce", "recent_blockhashes_sysvar", "nonce_authority"]),
+        SystemInstruction::WithdrawNonceAccount(lamports) => Decoded::new(program, "WithdrawNonceAccount",
+            json!({ "lamports": lamports }),
+            &["nonce", "recipient", "recent_blockhashes_sysvar", "rent_sysvar", "nonce_authority"]),
+        SystemInstruction::InitializeNonceAccount(authority) => Decoded::new(program, "InitializeNonceAccount",
+            json!({ "nonce_authority": authority.to_string() }),
+            &["nonce", "recent_blockhashes_sysvar", "rent_sysvar"]),
+        SystemInstruction::AuthorizeNonceAccount(authority) => Decoded::new(program, "AuthorizeNonceAccount",
+            json!({ "new_nonce_authority": authority.to_string() }),
+            &["nonce", "nonce_authority"]),
+        SystemInstruction::Allocate { space } => Decoded::new(program, "Allocate",
+            json!({ "space": space }),
+            &["account"]),
+        SystemInstruction::AllocateWithSeed { base, seed, space, owner } => Decoded::new(program, "AllocateWithSeed",
+            json!({ "base": base.to_string(), "seed": seed, "space": space, "owner": owner.to_string() }),
+            &["account", "base"]),
+        SystemInstruction::AssignWithSeed { base, seed, owner } => Decoded::new(program, "AssignWithSeed",
+            json!({ "base": base.to_string(), "seed": seed, "owner": owner.to_string() }),
+            &["account", "base"]),
+        SystemInstruction::TransferWithSeed { lamports, from_seed, from_owner } => Decoded::new(program, "TransferWithSeed",
+            json!({ "lamports": lamports, "from_seed": from_seed, "from_owner": from_owner.to_string() }),
+            &["source", "source_base", "destination"]),
+        SystemInstruction::UpgradeNonceAccount => Decoded::new(program, "UpgradeNonceAccount",
+            json!({}),
+            &["nonce"]),
+    })
+}
+
+// Both token programs share the instruction encoding for everything the
+// original program supports, so Token-2022's unpack reads either.
+#[allow(deprecated)]
+fn decode_token(program: &'static str, bytes: &[u8]) -> Result<Decoded, String> {
+    let instruction = TokenInstruction::unpack(bytes).map_err(|_| "Invalid token instruction data".to_string())?;
+
+    Ok(match instruction {
+        TokenInstruction::InitializeMint { decimals, mint_authority, freeze_authority } => Decoded::new(program, "InitializeMint",
+            json!({ "decimals": decimals, "mint_authority": mint_authority.to_string(), "freeze_authority": coption_address(&freeze_authority) }),
+            &["mint", "rent_sysvar"]),
+        TokenInstruction::InitializeAccount => Decoded::new(program, "InitializeAccount",
+            json!({}),
+            &["account", "mint", "owner", "rent_sysvar"]),
+        TokenInstruction::InitializeMultisig { m } => Decoded::new(program, "InitializeMultisig",
+            json!({ "m": m }),
+            &["multisig", "rent_sysvar"]).signers(),
+        TokenInstruction::Transfer { amount } => Decoded::new(program, "Transfer",
+            json!({ "amount": amount }),
+            &["source", "destination", "authority"]).signers(),
+        TokenInstruction::Approve { amount } => Decoded::new(program, "Approve",
+            json!({ "amount": amount }),
+            &["source", "delegate", "owner"]).signers(),
+        TokenInstruction::Revoke => Decoded::new(program, "Revoke",
+            json!({}),
+            &["source", "owner"]).signers(),
+        TokenInstruction::SetAuthority { authority_type, new_authority } => Decoded::new(program, "SetAuthority",
+            json!({ "authority_type": format!("{:?}", authority_type), "new_authority": coption_address(&new_authority) }),
+            &["account", "authority"]).signers(),
+        TokenInstruction::MintTo { amount } => Decoded::new(program, "MintTo",
+            json!({ "amount": amount }),
+            &["mint", "destination", "authority"]).signers(),
+        TokenInstruction::Burn { amount } => Decoded::new(program, "Burn",
+            json!({ "amount": amount }),
+            &["account", "mint", "authority"]).signers(),
+        TokenInstruction::CloseAccount => Decoded::new(program, "CloseAccount",
+            json!({}),
+            &["account", "destination", "authority"]).signers(),
+        TokenInstruction::FreezeAccount => Decoded::new(program, "FreezeAccount",
+            json!({}),
+            &["account", "mint", "authority"]).signers(),
+        TokenInstruction::ThawAccount => Decoded::new(program, "ThawAccount",
+            json!({}),
+            &["account", "mint", "authority"]).signers(),
+        TokenInstruction::TransferChecked { amount, decimals } => Decoded::new(program, "TransferChecked",
+            json!({ "amount": amount, "decimals": decimals }),
+            &["source", "mint", "destination", "authority"]).signers(),
+        TokenInstruction::ApproveChecked { amount, decimals } => Decoded::new(program, "ApproveChecked",
+            json!({ "amount": amount, "decimals": decimals }),
+            &["source", "mint", "delegate", "owner"]).signers(),
+        TokenInstruction::MintToChecked { amount, decimals } => Decoded::new(program, "MintToChecked",
+            json!({ "amount": amount, "decimals": decimals }),
+            &["mint", "destination", "authority"]).signers(),
+        TokenInstruction::BurnChecked { amount, decimals } => Decoded::new(program, "BurnChecked",
+            json!({ "amount": amount, "decimals": decimals }),
+            &["account", "mint", "authority"]).signers(),
+        TokenInstruction::InitializeAccount2 { owner } => Decoded::new(program, "InitializeAccount2",
+            json!({ "owner": owner.to_string() }),
+            &["account", "mint", "rent_sysvar"]),
+        TokenInstruction::SyncNative => Decoded::new(program, "SyncNative",
+            json!({}),
+            &["account"]),
+        TokenInstruction::InitializeAccount3 { owner } => Decoded::new(program, "InitializeAccount3",
+            json!({ "owner": owner.to_string() }),
+            &["account", "mint"]),
+        TokenInstruction::InitializeMultisig2 { m } => Decoded::new(program, "InitializeMultisig2",
+            json!({ "m": m }),
+            &["multisig"]).signers(),
+        TokenInstruction::InitializeMint2 { decimals, mint_authority, freeze_authority } => Decoded::new(program, "InitializeMint2",
+            json!({ "decimals": decimals, "mint_authority": mint_authority.to_string(), "freeze_authority": coption_address(&freeze_authority) }),
+            &["mint"]),
+        TokenInstruction::GetAccountDataSize { extension_types } => Decoded::new(program, "GetAccountDataSize",
+            json!({ "extension_types": extension_names(&extension_types) }),
+            &["mint"]),
+        TokenInstruction::InitializeImmutableOwner => Decoded::new(program, "InitializeImmutableOwner",
+            json!({}),
+            &["account"]),
+        TokenInstruction::AmountToUiAmount { amount } => Decoded::new(program, "AmountToUiAmount",
+            json!({ "amount": amount }),
+            &["mint"]),
+        TokenInstruction::UiAmountToAmount { ui_amount } => Decoded::new(program, "UiAmountToAmount",
+            json!({ "ui_amount": ui_amount }),
+            &["mint"]),
+        TokenInstruction::InitializeMintCloseAuthority { close_authority } => Decoded::new(program, "InitializeMintCloseAuthority",
+            json!({ "close_authority": coption_address(&close_authority) }),
+            &["mint"]),
+        TokenInstruction::Reallocate { extension_types } => Decoded::new(program, "Reallocate",
+            json!({ "extension_types": extension_names(&extension_types) }),
+            &["account", "payer", "system_program", "owner"]).signers(),
+        TokenInstruction::CreateNativeMint => Decoded::new(program, "CreateNativeMint",
+            json!({}),
+            &["payer", "native_mint", "system_program"]),
+        TokenInstruction::InitializeNonTransferableMint => Decoded::new(program, "InitializeNonTransferableMint",
+            json!({}),
+            &["mint"]),
+        TokenInstruction::InitializePermanentDelegate { delegate } => Decoded::new(program, "InitializePermanentDelegate",
+            json!({ "delegate": delegate.to_string() }),
+            &["mint"]),
+        TokenInstruction::WithdrawExcessLamports => Decoded::new(program, "WithdrawExcessLamports",
+            json!({}),
+            &["source", "destination", "authority"]).signers(),
+        // Extension instructions carry their own sub-instruction encoding;
+        // only the family is named and the accounts are left generic.
+        TokenInstruction::TransferFeeExtension => token_extension(program, "TransferFeeExtension", bytes),
+        TokenInstruction::ConfidentialTransferExtension => token_extension(program, "ConfidentialTransferExtension", bytes),
+        TokenInstruction::DefaultAccountStateExtension => token_extension(program, "DefaultAccountStateExtension", bytes),
+        TokenInstruction::MemoTransferExtension => token_extension(program, "MemoTransferExtension", bytes),
+        TokenInstruction::InterestBearingMintExtension => token_extension(program, "InterestBearingMintExtension", bytes),
+        TokenInstruction::CpiGuardExtension => token_extension(program, "CpiGuardExtension", bytes),
+        TokenInstruction::TransferHookExtension => token_extension(program, "TransferHookExtension", bytes),
+        TokenInstruction::ConfidentialTransferFeeExtension => token_extension(program, "ConfidentialTransferFeeExtension", bytes),
+        TokenInstruction::MetadataPointerExtension => token_extension(program, "MetadataPointerExtension", bytes),
+        TokenInstruction::GroupPointerExtension => token_extension(program, "GroupPointerExtension", bytes),
+        TokenInstruction::GroupMemberPointerExtension => token_extension(program, "GroupMemberPointerExtension", bytes),
+        TokenInstruction::ConfidentialMintBurnExtension => token_extension(program, "ConfidentialMintBurnExtension", bytes),
+        TokenInstruction::ScaledUiAmountExtension => token_extension(program, "ScaledUiAmountExtension", bytes),
+        TokenInstruction::PausableExtension => token_extension(program, "PausableExtension", bytes),
+    })
+}
+
+// An empty payload is the original Create instruction
+fn decode_associated_token(bytes: &[u8]) -> Result<Decoded, String> {
+    let program = "spl-associated-token-account";
+    let create_roles: &'static [&'static str] = &["funding", "associated_token_account", "wallet", "mint", "system_program", "token_program"];
+
+    match bytes {
+        [] | [0] => Ok(Decoded::new(program, "Create", json!({}), create_roles)),
+        [1] => Ok(Decoded::new(program, "CreateIdempotent", json!({}), create_roles)),
+        [2] => Ok(Decoded::new(program, "RecoverNested", json!({}), &[
+            "nested_associated_token_account",
+            "nested_mint",
+            "destination_associated_token_account",
+            "owner_associated_token_account",
+            "owner_mint",
+            "wallet",
+            "token_program",
+        ])),
+        _ => Err("Invalid associated token account instruction data".to_string()),
+    }
+}
+
+fn decode_memo(bytes: &[u8]) -> Result<Decoded, String> {
+    let memo = std::str::from_utf8(bytes).map_err(|_| "Memo is not valid UTF-8".to_string())?;
+    Ok(Decoded::new("spl-memo", "Memo", json!({ "memo": memo }), &[]).rest("signer"))
+}
+
+fn decode_compute_budget(bytes: &[u8]) -> Result<Decoded, String> {
+    let program = "compute-budget";
+
+    Ok(match parse_compute_budget(bytes)? {
+        ComputeBudgetInstruction::RequestHeapFrame(bytes) => Decoded::new(program, "RequestHeapFrame", json!({ "bytes": bytes }), &[]),
+        ComputeBudgetInstruction::SetComputeUnitLimit(units) => Decoded::new(program, "SetComputeUnitLimit", json!({ "units": units }), &[]),
+        ComputeBudgetInstruction::SetComputeUnitPrice(micro_lamports) => {
+            Decoded::new(program, "SetComputeUnitPrice", json!({ "micro_lamports": micro_lamports }), &[])
+        }
+        ComputeBudgetInstruction::SetLoadedAccountsDataSizeLimit(bytes) => {
+            Decoded::new(program, "SetLoadedAccountsDataSizeLimit", json!({ "bytes": bytes }), &[])
+        }
+        ComputeBudgetInstruction::Unused => return Err("Invalid compute budget instruction data".to_string()),
+    })
+}
+
+// Also used by the fee estimator, which reads a transaction's budget
+pub(crate) fn parse_compute_budget(bytes: &[u8]) -> Result<ComputeBudgetInstruction, String> {
+    borsh::from_slice(bytes).map_err(|_| "Invalid compute budget instruction data".to_string())
+}
+
+#[allow(deprecated)]
+fn decode_stake(bytes: &[u8]) -> Result<Decoded, String> {
+    let instruction: StakeInstruction = bincode::deserialize(bytes).map_err(|_| "Invalid stake instruction data".to_string())?;
+    let program = "stake";
+
+    Ok(match instruction {
+        StakeInstruction::Initialize(authorized, lockup) => Decoded::new(program, "Initialize",
+            json!({
+                "staker": authorized.staker.to_string(),
+                "withdrawer": authorized.withdrawer.to_string(),
+                "lockup": {
+                    "unix_timestamp": lockup.unix_timestamp,
+                    "epoch": lockup.epoch,
+                    "custodian": lockup.custodian.to_string(),
+                },
+            }),
+            &["stake", "rent_sysvar"]),
+        StakeInstruction::Authorize(new_authority, stake_authorize) => Decoded::new(program, "Authorize",
+            json!({ "new_authority": new_authority.to_string(), "authorize_type": format!("{:?}", stake_authorize) }),
+            &["stake", "clock_sysvar", "authority"]).optional(&["custodian"]),
+        StakeInstruction::DelegateStake => Decoded::new(program, "DelegateStake",
+            json!({}),
+            &["stake", "vote", "clock_sysvar", "stake_history_sysvar", "stake_config", "stake_authority"]),
+        StakeInstruction::Split(lamports) => Decoded::new(program, "Split",
+            json!({ "lamports": lamports }),
+            &["stake", "split_stake", "stake_authority"]),
+        StakeInstruction::Withdraw(lamports) => Decoded::new(program, "Withdraw",
+            json!({ "lamports": lamports }),
+            &["stake", "recipient", "clock_sysvar", "stake_history_sysvar", "withdraw_authority"]).optional(&["custodian"]),
+        StakeInstruction::Deactivate => Decoded::new(program, "Deactivate",
+            json!({}),
+            &["stake", "clock_sysvar", "stake_authority"]),
+        StakeInstruction::SetLockup(args) => Decoded::new(program, "SetLockup",
+            json!({
+                "unix_timestamp": args.unix_timestamp,
+                "epoch": args.epoch,
+                "custodian": args.custodian.map(|custodian| custodian.to_string()),
+            }),
+            &["stake", "authority"]),
+        StakeInstruction::Merge => Decoded::new(program, "Merge",
+            json!({}),
+            &["destination", "source", "clock_sysvar", "stake_history_sysvar", "stake_authority"]),
+        StakeInstruction::AuthorizeWithSeed(args) => Decoded::new(program, "AuthorizeWithSeed",
+            json!({
+                "new_authority": args.new_authorized_pubkey.to_string(),
+                "authorize_type": format!("{:?}", args.stake_authorize),
+                "authority_seed": args.authority_seed,
+                "authority_owner": args.authority_owner.to_string(),
+            }),
+            &["stake", "base", "clock_sysvar"]).optional(&["custodian"]),
+        StakeInstruction::InitializeChecked => Decoded::new(program, "InitializeChecked",
+            json!({}),
+            &["stake", "rent_sysvar", "staker", "withdrawer"]),
+        StakeInstruction::AuthorizeChecked(stake_authorize) => Decoded::new(program, "AuthorizeChecked",
+            json!({ "authorize_type": format!("{:?}", stake_authorize) }),
+            &["stake", "clock_sysvar", "authority", "new_authority"]).optional(&["custodian"]),
+        StakeInstruction::AuthorizeCheckedWithSeed(args) => Decoded::new(program, "AuthorizeCheckedWithSeed",
+            json!({
+                "authorize_type": format!("{:?}", args.stake_authorize),
+                "authority_seed": args.authority_seed,
+                "authority_owner": args.authority_owner.to_string(),
+            }),
+            &["stake", "base", "clock_sysvar", "new_authority"]).optional(&["custodian"]),
+        StakeInstruction::SetLockupChecked(args) => Decoded::new(program, "SetLockupChecked",
+            json!({ "unix_timestamp": args.unix_timestamp, "epoch": args.epoch }),
+            &["stake", "authority"]).optional(&["new_custodian"]),
+        StakeInstruction::GetMinimumDelegation => Decoded::new(program, "GetMinimumDelegation",
+            json!({}),
+            &[]),
+        StakeInstruction::DeactivateDelinquent => Decoded::new(program, "DeactivateDelinquent",
+            json!({}),
+            &["stake", "delinquent_vote", "reference_vote"]),
+        StakeInstruction::Redelegate => Decoded::new(program, "Redelegate",
+            json!({}),
+            &["stake", "uninitialized_stake", "vote", "stake_config", "stake_authority"]),
+        StakeInstruction::MoveStake(lamports) => Decoded::new(program, "MoveStake",
+            json!({ "lamports": lamports }),
+            &["source", "destination", "stake_authority"]),
+        StakeInstruction::MoveLamports(lamports) => Decoded::new(program, "MoveLamports",
+            json!({ "lamports": lamports }),
+            &["source", "destination", "stake_authority"]),
+    })
+}
+
+fn decode_lookup_table(bytes: &[u8]) -> Result<Decoded, String> {
+    let instruction: LookupTableInstruction = bincode::deserialize(bytes).map_err(|_| "Invalid lookup table instruction data".to_string())?;
+    let program = "address-lookup-table";
+
+    Ok(match instruction {
+        LookupTableInstruction::CreateLookupTable { recent_slot, bump_seed } => Decoded::new(program, "CreateLookupTable",
+            json!({ "recent_slot": recent_slot, "bump_seed": bump_seed }),
+            &["lookup_table", "authority", "payer", "system_program"]),
+        LookupTableInstruction::FreezeLookupTable => Decoded::new(program, "FreezeLookupTable",
+            json!({}),
+            &["lookup_table", "authority"]),
+        LookupTableInstruction::ExtendLookupTable { new_addresses } => Decoded::new(program, "ExtendLookupTable",
+            json!({ "new_addresses": new_addresses.iter().map(Pubkey::to_string).collect::<Vec<_>>() }),
+            &["lookup_table", "authority"]).optional(&["payer", "system_program"]),
+        LookupTableInstruction::DeactivateLookupTable => Decoded::new(program, "DeactivateLookupTable",
+            json!({}),
+            &["lookup_table", "authority"]),
+        LookupTableInstruction::CloseLookupTable => Decoded::new(program, "CloseLookupTable",
+            json!({}),
+            &["lookup_table", "authority", "recipient"]),
+    })
+}
+
+fn decode_upgradeable_loader(bytes: &[u8]) -> Result<Decoded, String> {
+    let instruction: UpgradeableLoaderInstruction = bincode::deserialize(bytes).map_err(|_| "Invalid upgradeable loader instruction data".to_string())?;
+    let program = "bpf-upgradeable-loader";
+
+    Ok(match instruction {
+        UpgradeableLoaderInstruction::InitializeBuffer => Decoded::new(program, "InitializeBuffer",
+            json!({}),
+            &["buffer"]).optional(&["authority"]),
+        UpgradeableLoaderInstruction::Write { offset, bytes } => Decoded::new(program, "Write",
+            json!({ "offset": offset, "bytes": bytes.len() }),
+            &["buffer", "authority"]),
+        UpgradeableLoaderInstruction::DeployWithMaxDataLen { max_data_len } => Decoded::new(program, "DeployWithMaxDataLen",
+            json!({ "max_data_len": max_data_len }),
+            &["payer", "program_data", "program", "buffer", "rent_sysvar", "clock_sysvar", "system_program", "authority"]),
+        UpgradeableLoaderInstruction::Upgrade => Decoded::new(program, "Upgrade",
+            json!({}),
+            &["program_data", "program", "buffer", "spill", "rent_sysvar", "clock_sysvar", "authority"]),
+        UpgradeableLoaderInstruction::SetAuthority => Decoded::new(program, "SetAuthority",
+            json!({}),
+            &["account", "authority"]).optional(&["new_authority"]),
+        UpgradeableLoaderInstruction::Close => Decoded::new(program, "Close",
+            json!({}),
+            &["account", "recipient"]).optional(&["authority", "program"]),
+        UpgradeableLoaderInstruction::ExtendProgram { additional_bytes } => Decoded::new(program, "ExtendProgram",
+            json!({ "additional_bytes": additional_bytes }),
+            &["program_data", "program"]).optional(&["system_program", "payer"]),
+        UpgradeableLoaderInstruction::SetAuthorityChecked => Decoded::new(program, "SetAuthorityChecked",
+            json!({}),
+            &["account", "authority", "new_authority"]),
+        UpgradeableLoaderInstruction::Migrate => Decoded::new(program, "Migrate",
+            json!({}),
+            &["program_data", "program", "authority"]),
+        UpgradeableLoaderInstruction::ExtendProgramChecked { additional_bytes } => Decoded::new(program, "ExtendProgramChecked",
+            json!({ "additional_bytes": additional_bytes }),
+            &["program_data", "program", "authority"]).optional(&["system_program", "payer"]),
+    })
+}
+
+fn token_extension(program: &'static str, name: &'static str, bytes: &[u8]) -> Decoded {
+    Decoded::new(program, name, json!({ "extension_data": hex::encode(&bytes[1..]) }), &[]).rest("account")
+}
+
+// Same camelCase names /account/decode reports
+fn extension_names(extension_types: &[ExtensionType]) -> Vec<String> {
+    extension_types.iter().copied().map(extension_name).collect()
+}
@@ -0,0 +1,367 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use aes_gcm::{aead::{Aead, KeyInit, Payload}, Aes256Gcm, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::RngCore;
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+use crate::{error_response, json_response, keypair, JsonResponse};
+
+const KEY_STORE_VERSION: u8 = 1;
+
+// Sealed into every store file so a passphrase can be checked even when the
+// store holds no keys yet
+const PASSPHRASE_CHECK: &[u8] = b"key store passphrase check";
+
+// Argon2id defaults: 64 MiB, 3 passes, 1 lane
+pub const DEFAULT_M_COST: u32 = 64 * 1024;
+pub const DEFAULT_T_COST: u32 = 3;
+pub const DEFAULT_P_COST: u32 = 1;
+
+pub struct KeyStore {
+    path: PathBuf,
+    cipher: Aes256Gcm,
+    kdf: KdfParams,
+    check: PassphraseCheck,
+    keys: BTreeMap<String, StoredKey>,
+}
+
+struct StoredKey {
+    keypair: Keypair,
+    label: Option<String>,
+    created_at: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct KeyStoreFile {
+    version: u8,
+    kdf: KdfParams,
+    check: PassphraseCheck,
+    keys: Vec<EncryptedKey>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct PassphraseCheck {
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct KdfParams {
+    algorithm: String,
+    salt: String,
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EncryptedKey {
+    key_id: String,
+    pubkey: String,
+    label: Option<String>,
+    created_at: u64,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Deserialize)]
+struct GenerateKeyRequest {
+    #[serde(default)]
+    label: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ImportKeyRequest {
+    secret: Value,
+    #[serde(default)]
+    format: Option<String>,
+    #[serde(default)]
+    label: Option<String>,
+}
+
+#[derive(Serialize)]
+struct KeyResponse {
+    success: bool,
+    data: KeyData,
+}
+
+#[derive(Serialize)]
+struct KeyListResponse {
+    success: bool,
+    data: Vec<KeyData>,
+}
+
+#[derive(Serialize)]
+pub struct KeyData {
+    #[serde(rename = "keyId")]
+    key_id: String,
+    pubkey: String,
+    label: Option<String>,
+    created_at: u64,
+}
+
+impl KdfParams {
+    fn new() -> Self {
+        let mut salt = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut salt);
+        KdfParams {
+            algorithm: "argon2id".to_string(),
+            salt: hex::encode(salt),
+            m_cost: DEFAULT_M_COST,
+            t_cost: DEFAULT_T_COST,
+            p_cost: DEFAULT_P_COST,
+        }
+    }
+
+    fn derive_cipher(&self, passphrase: &str) -> Result<Aes256Gcm, String> {
+        if self.algorithm != "argon2id" {
+            return Err(format!("Unsupported key derivation algorithm: {}", self.algorithm));
+        }
+
+        let salt = hex::decode(&self.salt).map_err(|_| "Invalid key store salt")?;
+        let params = Params::new(self.m_cost, self.t_cost, self.p_cost, Some(32))
+            .map_err(|err| format!("Invalid key derivation parameters: {}", err))?;
+
+        let mut key = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .map_err(|err| format!("Key derivation failed: {}", err))?;
+
+        Ok(Aes256Gcm::new(&key.into()))
+    }
+}
+
+impl PassphraseCheck {
+    fn seal(cipher: &Aes256Gcm) -> Result<Self, String> {
+        let mut nonce = [0u8; 12];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = cipher.encrypt(Nonce::from_slice(&nonce), PASSPHRASE_CHECK)
+            .map_err(|_| "Failed to encrypt passphrase check")?;
+        Ok(PassphraseCheck { nonce: hex::encode(nonce), ciphertext: hex::encode(ciphertext) })
+    }
+
+    fn verify(&self, cipher: &Aes256Gcm) -> bool {
+        let (Ok(nonce), Ok(ciphertext)) = (hex::decode(&self.nonce), hex::decode(&self.ciphertext)) else {
+            return false;
+        };
+        nonce.len() == 12
+            && cipher.decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice()).is_ok_and(|plaintext| plaintext == PASSPHRASE_CHECK)
+    }
+}
+
+impl KeyStore {
+    // Opens the store at `path`, creating an empty one if the file does not
+    // exist yet. The passphrase check and every entry are decrypted up front
+    // so a wrong passphrase is caught at startup, even for an empty store.
+    pub fn open(path: PathBuf, passphrase: &str) -> Result<KeyStore, String> {
+        if !path.exists() {
+            let kdf = KdfParams::new();
+            let cipher = kdf.derive_cipher(passphrase)?;
+            let check = PassphraseCheck::seal(&cipher)?;
+            let store = KeyStore { path, cipher, kdf, check, keys: BTreeMap::new() };
+            store.save()?;
+            return Ok(store);
+        }
+
+        let contents = fs::read_to_string(&path).map_err(|err| format!("Failed to read key store: {}", err))?;
+        let file: KeyStoreFile = serde_json::from_str(&contents).map_err(|_| "Key store file is corrupt")?;
+        if file.version != KEY_STORE_VERSION {
+            return Err(format!("Unsupported key store version: {}", file.version));
+        }
+
+        let cipher = file.kdf.derive_cipher(passphrase)?;
+        if !file.check.verify(&cipher) {
+            return Err("Wrong master passphrase or tampered key store".to_string());
+        }
+
+        let mut keys = BTreeMap::new();
+        for entry in file.keys {
+            let nonce = hex::decode(&entry.nonce).map_err(|_| "Key store file is corrupt")?;
+            let ciphertext = hex::decode(&entry.ciphertext).map_err(|_| "Key store file is corrupt")?;
+            if nonce.len() != 12 {
+                return Err("Key store file is corrupt".to_string());
+            }
+
+            let secret = cipher
+                .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: entry.key_id.as_bytes() })
+                .map_err(|_| "Wrong master passphrase or tampered key store")?;
+            let keypair = Keypair::try_from(secret.as_slice()).map_err(|_| "Key store file is corrupt")?;
+            if keypair.pubkey().to_string() != entry.pubkey {
+                return Err(format!("Stored pubkey does not match key {}", entry.key_id));
+            }
+
+            keys.insert(entry.key_id, StoredKey { keypair, label: entry.label, created_at: entry.created_at });
+        }
+
+        Ok(KeyStore { path, cipher, kdf: file.kdf, check: file.check, keys })
+    }
+
+    // For routes that hand a stored key outside the server in any form
+    pub fn verify_passphrase(&self, passphrase: &str) -> bool {
+        self.kdf.derive_cipher(passphrase).is_ok_and(|cipher| self.check.verify(&cipher))
+    }
+
+    pub fn keypair(&self, key_id: &str) -> Option<&Keypair> {
+        self.keys.get(key_id).map(|stored| &stored.keypair)
+    }
+
+    pub fn insert(&mut self, keypair: Keypair, label: Option<String>) -> Result<KeyData, String> {
+        let mut id_bytes = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut id_bytes);
+        let key_id = format!("key_{}", hex::encode(id_bytes));
+
+        let created_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let data = KeyData { key_id: key_id.clone(), pubkey: keypair.pubkey().to_string(), label: label.clone(), created_at };
+
+        self.keys.insert(key_id.clone(), StoredKey { keypair, label, created_at });
+        if let Err(err) = self.save() {
+            self.keys.remove(&key_id);
+            return Err(err);
+        }
+
+        Ok(data)
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let mut entries = Vec::with_capacity(self.keys.len());
+        for (key_id, stored) in &self.keys {
+            let mut nonce = [0u8; 12];
+            rand::thread_rng().fill_bytes(&mut nonce);
+
+            let ciphertext = self.cipher
+                .encrypt(Nonce::from_slice(&nonce), Payload { msg: &stored.keypair.to_bytes(), aad: key_id.as_bytes() })
+                .map_err(|_| "Failed to encrypt key")?;
+
+            entries.push(EncryptedKey {
+                key_id: key_id.clone(),
+                pubkey: stored.keypair.pubkey().to_string(),
+                label: stored.label.clone(),
+                created_at: stored.created_at,
+                nonce: hex::encode(nonce),
+                ciphertext: hex::encode(ciphertext),
+            });
+        }
+
+        let file = KeyStoreFile {
+            version: KEY_STORE_VERSION,
+            kdf: self.kdf.clone(),
+            check: self.check.clone(),
+            keys: entries,
+        };
+        let json = serde_json::to_string_pretty(&file).unwrap();
+
+        // Write to a sibling file and rename so a crash never leaves a half-written store
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, json).map_err(|err| format!("Failed to write key store: {}", err))?;
+        fs::rename(&tmp_path, &self.path).map_err(|err| format!("Failed to write key store: {}", err))
+    }
+}
+
+pub fn not_configured() -> JsonResponse {
+    error_response(503, "Key store is not configured; set KEYSTORE_PASSPHRASE")
+}
+
+// Resolves a `keyId` for signing routes, responding with the error to send
+// back when the store is disabled or the id is unknown.
+pub fn lookup(store: Option<&KeyStore>, key_id: &str) -> Result<Keypair, JsonResponse> {
+    let store = store.ok_or_else(not_configured)?;
+    store.keypair(key_id)
+        .map(|kp| kp.insecure_clone())
+        .ok_or_else(|| error_response(404, "Unknown key id"))
+}
+
+pub fn generate(store: Option<&mut KeyStore>, content: &str) -> JsonResponse {
+    let store = match store {
+        Some(store) => store,
+        None => return not_configured(),
+    };
+
+    let content = if content.trim().is_empty() { "{}" } else { content };
+    let data: GenerateKeyRequest = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    match store.insert(Keypair::new(), data.label) {
+        Ok(key) => json_response(200, serde_json::to_string(&KeyResponse { success: true, data: key }).unwrap()),
+        Err(error) => error_response(500, &error),
+    }
+}
+
+pub fn import(store: Option<&mut KeyStore>, content: &str) -> JsonResponse {
+    let store = match store {
+        Some(store) => store,
+        None => return not_configured(),
+    };
+
+    let data: ImportKeyRequest = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    let (keypair, _) = match keypair::decode_secret(&data.secret, data.format.as_deref()) {
+        Ok(decoded) => decoded,
+        Err(error) => return error_response(400, &error),
+    };
+
+    match store.insert(keypair, data.label) {
+        Ok(key) => json_response(200, serde_json::to_string(&KeyResponse { success: true, data: key }).unwrap()),
+        Err(error) => error_response(500, &error),
+    }
+}
+
+pub fn list(store: Option<&KeyStore>) -> JsonResponse {
+    let store = match store {
+        Some(store) => store,
+        None => return not_configured(),
+    };
+
+    let keys = store.keys.iter().map(|(key_id, stored)| KeyData {
+        key_id: key_id.clone(),
+        pubkey: stored.keypair.pubkey().to_string(),
+        label: stored.label.clone(),
+        created_at: stored.created_at,
+    }).collect();
+
+    json_response(200, serde_json::to_string(&KeyListResponse { success: true, data: keys }).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path() -> PathBuf {
+        let mut name = [0u8; 8];
+        rand::thread_rng().fill_bytes(&mut name);
+        std::env::temp_dir().join(format!("key_store_{}.json", hex::encode(name)))
+    }
+
+    #[test]
+    fn empty_store_rejects_wrong_passphrase() {
+        let path = temp_path();
+        KeyStore::open(path.clone(), "right").unwrap();
+
+        let result = KeyStore::open(path.clone(), "wrong");
+        let _ = fs::remove_file(&path);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn store_reopens_with_its_keys() {
+        let path = temp_path();
+        let pubkey = {
+            let mut store = KeyStore::open(path.clone(), "right").unwrap();
+            store.insert(Keypair::new(), None).unwrap().pubkey
+        };
+
+        let store = KeyStore::open(path.clone(), "right");
+        let _ = fs::remove_file(&path);
+        let store = store.unwrap();
+        assert_eq!(store.keys.values().next().unwrap().keypair.pubkey().to_string(), pubkey);
+    }
+}
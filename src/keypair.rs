@@ -0,0 +1,312 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::{Serialize, Deserialize};
+use serde_json::{json, Value};
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+use crate::{error_response, json_response, JsonResponse};
+
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const MAX_VANITY_THREADS: usize = 64;
+const MAX_VANITY_TIMEOUT_MS: u64 = 120_000;
+
+// A base58 public key is at most 44 characters long
+const MAX_PATTERN_LEN: usize = 44;
+
+// Searches run off the request loop; one at a time keeps the thread count bounded
+static SEARCH_RUNNING: AtomicBool = AtomicBool::new(false);
+
+// The right to run the one vanity search. It is claimed before a search thread
+// is spawned and released on drop, so a panicking search cannot leak it.
+pub struct SearchSlot(());
+
+impl SearchSlot {
+    pub fn claim() -> Option<SearchSlot> {
+        if SEARCH_RUNNING.swap(true, Ordering::Acquire) {
+            None
+        } else {
+            Some(SearchSlot(()))
+        }
+    }
+}
+
+impl Drop for SearchSlot {
+    fn drop(&mut self) {
+        SEARCH_RUNNING.store(false, Ordering::Release);
+    }
+}
+
+#[derive(Deserialize)]
+struct ConvertKeypairRequest {
+    secret: Value,
+    #[serde(default)]
+    format: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ConvertKeypairResponse {
+    success: bool,
+    data: ConvertKeypairData,
+}
+
+#[derive(Serialize)]
+struct ConvertKeypairData {
+    pubkey: String,
+    detected_format: &'static str,
+    base58: String,
+    json: Vec<u8>,
+    hex: String,
+    seed_base58: String,
+    seed_hex: String,
+}
+
+#[derive(Deserialize)]
+struct VanityRequest {
+    #[serde(default)]
+    prefix: String,
+    #[serde(default)]
+    suffix: String,
+    #[serde(rename = "caseSensitive", default = "default_case_sensitive")]
+    case_sensitive: bool,
+    #[serde(default)]
+    threads: Option<usize>,
+    #[serde(rename = "timeoutMs", default = "default_vanity_timeout_ms")]
+    timeout_ms: u64,
+}
+
+#[derive(Serialize)]
+struct VanityResponse {
+    success: bool,
+    data: VanityData,
+}
+
+#[derive(Serialize)]
+struct VanityData {
+    pubkey: String,
+    secret: String,
+    attempts: u64,
+    elapsed_ms: u128,
+    threads: usize,
+}
+
+fn default_case_sensitive() -> bool {
+    true
+}
+
+fn default_vanity_timeout_ms() -> u64 {
+    30_000
+}
+
+// Parses a secret in any supported format, returning the keypair and the
+// format it was recognised as. `format` forces a specific interpretation.
+pub fn decode_secret(secret: &Value, format: Option<&str>) -> Result<(Keypair, &'static str), String> {
+    if let Some(other) = format.filter(|f| !matches!(*f, "json" | "hex" | "base58")) {
+        return Err(format!("Unsupported format: {}", other));
+    }
+
+    let (bytes, detected) = match (secret, format) {
+        (Value::Array(values), None | Some("json")) => {
+            let bytes = values.iter()
+                .map(|v| v.as_u64().and_then(|n| u8::try_from(n).ok()))
+                .collect::<Option<Vec<u8>>>()
+                .ok_or("JSON secret must be an array of bytes")?;
+            (bytes, "json")
+        }
+        (Value::String(s), Some("hex")) => (hex::decode(s).map_err(|_| "Invalid hex encoding")?, "hex"),
+        (Value::String(s), Some("base58")) => (bs58::decode(s).into_vec().map_err(|_| "Invalid base58 encoding")?, "base58"),
+        (Value::String(s), Some("json")) => {
+            let bytes: Vec<u8> = serde_json::from_str(s).map_err(|_| "JSON secret must be an array of bytes")?;
+            (bytes, "json")
+        }
+        (Value::String(s), None) => {
+            let s = s.trim();
+            if s.starts_with('[') {
+                let bytes: Vec<u8> = serde_json::from_str(s).map_err(|_| "JSON secret must be an array of bytes")?;
+                (bytes, "json")
+            } else if (s.len() == 64 || s.len() == 128) && s.chars().all(|c| c.is_ascii_hexdigit()) {
+                (hex::decode(s).map_err(|_| "Invalid hex encoding")?, "hex")
+            } else {
+                (bs58::decode(s).into_vec().map_err(|_| "Unrecognised secret encoding")?, "base58")
+            }
+        }
+        _ => return Err("Secret must be a string or an array of bytes".to_string()),
+    };
+
+    match bytes.len() {
+        32 => {
+            let seed: [u8; 32] = bytes.try_into().unwrap();
+            Ok((Keypair::new_from_array(seed), "seed"))
+        }
+        64 => {
+            let keypair = Keypair::try_from(bytes.as_slice())
+                .map_err(|_| "Embedded pubkey does not match the secret key")?;
+            Ok((keypair, detected))
+        }
+        len => Err(format!("Secret must be 32 or 64 bytes, got {}", len)),
+    }
+}
+
+pub fn convert(content: &str) -> JsonResponse {
+    let data: ConvertKeypairRequest = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    let (keypair, detected_format) = match decode_secret(&data.secret, data.format.as_deref()) {
+        Ok(decoded) => decoded,
+        Err(error) => return error_response(400, &error),
+    };
+
+    let bytes = keypair.to_bytes();
+    let seed = keypair.secret_bytes();
+
+    let response_data = ConvertKeypairResponse {
+        success: true,
+        data: ConvertKeypairData {
+            pubkey: keypair.pubkey().to_string(),
+            detected_format,
+            base58: bs58::encode(bytes).into_string(),
+            json: bytes.to_vec(),
+            hex: hex::encode(bytes),
+            seed_base58: bs58::encode(seed).into_string(),
+            seed_hex: hex::encode(seed),
+        },
+    };
+
+    json_response(200, serde_json::to_string(&response_data).unwrap())
+}
+
+pub fn vanity(content: &str, _slot: SearchSlot) -> JsonResponse {
+    let data: VanityRequest = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    if data.prefix.is_empty() && data.suffix.is_empty() {
+        return error_response(400, "A prefix or suffix is required");
+    }
+
+    if data.prefix.chars().count() + data.suffix.chars().count() > MAX_PATTERN_LEN {
+        return error_response(400, "Prefix and suffix together must be at most 44 characters");
+    }
+
+    // Case-insensitive patterns only need one casing of each character to exist
+    let in_alphabet = |c: char| {
+        BASE58_ALPHABET.contains(c)
+            || (!data.case_sensitive
+                && (BASE58_ALPHABET.contains(c.to_ascii_lowercase()) || BASE58_ALPHABET.contains(c.to_ascii_uppercase())))
+    };
+    if let Some(c) = data.prefix.chars().chain(data.suffix.chars()).find(|c| !in_alphabet(*c)) {
+        return error_response(400, &format!("Pattern contains non-base58 character '{}'", c));
+    }
+
+    if data.timeout_ms == 0 || data.timeout_ms > MAX_VANITY_TIMEOUT_MS {
+        return error_response(400, "Timeout must be between 1 and 120000 ms");
+    }
+
+    let threads = match data.threads {
+        Some(n) if n == 0 || n > MAX_VANITY_THREADS => return error_response(400, "Threads must be between 1 and 64"),
+        Some(n) => n,
+        None => thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+    };
+
+    let (prefix, suffix) = if data.case_sensitive {
+        (data.prefix, data.suffix)
+    } else {
+        (data.prefix.to_lowercase(), data.suffix.to_lowercase())
+    };
+
+    search((prefix, suffix, data.case_sensitive), threads, data.timeout_ms)
+}
+
+fn search(pattern: (String, String, bool), threads: usize, timeout_ms: u64) -> JsonResponse {
+    let pattern = Arc::new(pattern);
+
+    let found = Arc::new(AtomicBool::new(false));
+    let attempts = Arc::new(AtomicU64::new(0));
+    let (sender, receiver) = mpsc::channel();
+    let start = Instant::now();
+    let deadline = start + Duration::from_millis(timeout_ms);
+
+    let workers = (0..threads).map(|_| {
+        let (pattern, found, attempts, sender) = (pattern.clone(), found.clone(), attempts.clone(), sender.clone());
+        thread::spawn(move || {
+            let (prefix, suffix, case_sensitive) = &*pattern;
+            let mut local_attempts = 0u64;
+            while !found.load(Ordering::Relaxed) {
+                let keypair = Keypair::new();
+                let address = keypair.pubkey().to_string();
+                let address = if *case_sensitive { address } else { address.to_lowercase() };
+                local_attempts += 1;
+
+                if address.starts_with(prefix.as_str()) && address.ends_with(suffix.as_str()) {
+                    found.store(true, Ordering::Relaxed);
+                    let _ = sender.send(keypair);
+                    break;
+                }
+
+                // Check the clock and publish progress every few thousand keys
+                if local_attempts.is_multiple_of(4096) {
+                    attempts.fetch_add(4096, Ordering::Relaxed);
+                    if Instant::now() >= deadline {
+                        break;
+                    }
+                }
+            }
+            attempts.fetch_add(local_attempts % 4096, Ordering::Relaxed);
+        })
+    }).collect::<Vec<_>>();
+    drop(sender);
+
+    let result = receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()));
+    found.store(true, Ordering::Relaxed);
+    for worker in workers {
+        let _ = worker.join();
+    }
+
+    let attempts = attempts.load(Ordering::Relaxed);
+    let elapsed_ms = start.elapsed().as_millis();
+
+    match result {
+        Ok(keypair) => {
+            let response_data = VanityResponse {
+                success: true,
+                data: VanityData {
+                    pubkey: keypair.pubkey().to_string(),
+                    secret: bs58::encode(keypair.to_bytes()).into_string(),
+                    attempts,
+                    elapsed_ms,
+                    threads,
+                },
+            };
+
+            json_response(200, serde_json::to_string(&response_data).unwrap())
+        }
+        Err(_) => json_response(
+            408,
+            json!({
+                "success": false,
+                "error": "No matching address found before the timeout",
+                "attempts": attempts,
+                "elapsed_ms": elapsed_ms,
+            }).to_string(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_slot_is_released_after_a_rejected_request() {
+        let content = json!({ "prefix": "a".repeat(40), "suffix": "b".repeat(5) }).to_string();
+        let slot = SearchSlot::claim().unwrap();
+        assert!(SearchSlot::claim().is_none());
+
+        assert_eq!(vanity(&content, slot).status_code().0, 400);
+        assert!(SearchSlot::claim().is_some());
+    }
+}
@@ -0,0 +1,334 @@
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use aes::Aes128;
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::RngCore;
+use sha3::{Digest, Keccak256};
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+use crate::key_store::{self, KeyStore, DEFAULT_M_COST, DEFAULT_P_COST, DEFAULT_T_COST};
+use crate::{error_response, json_response, keypair, JsonResponse};
+
+type Aes128Ctr = ctr::Ctr128BE<Aes128>;
+
+const KEYSTORE_FILE_VERSION: u8 = 1;
+const DERIVED_KEY_LEN: usize = 32;
+
+// Modelled on the Ethereum v3 keystore: the derived key's first half is the
+// AES-128-CTR key and its second half authenticates the ciphertext.
+#[derive(Serialize, Deserialize)]
+struct KeystoreFile {
+    version: u8,
+    id: String,
+    pubkey: String,
+    crypto: KeystoreCrypto,
+}
+
+#[derive(Serialize, Deserialize)]
+struct KeystoreCrypto {
+    cipher: String,
+    cipherparams: CipherParams,
+    ciphertext: String,
+    kdf: String,
+    kdfparams: KdfParams,
+    mac: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CipherParams {
+    iv: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct KdfParams {
+    dklen: usize,
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+    salt: String,
+}
+
+#[derive(Deserialize)]
+struct ExportRequest {
+    #[serde(default)]
+    secret: Option<Value>,
+    #[serde(rename = "keyId", default)]
+    key_id: Option<String>,
+    #[serde(rename = "masterPassphrase", default)]
+    master_passphrase: Option<String>,
+    password: String,
+}
+
+#[derive(Deserialize)]
+struct ImportRequest {
+    keystore: Value,
+    password: String,
+    #[serde(default)]
+    store: bool,
+    #[serde(default)]
+    label: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ExportResponse {
+    success: bool,
+    data: KeystoreFile,
+}
+
+#[derive(Serialize)]
+struct ImportResponse {
+    success: bool,
+    data: ImportData,
+}
+
+// The secret never goes back out; importing without storing only proves the
+// file and password are good
+#[derive(Serialize)]
+struct ImportData {
+    pubkey: String,
+}
+
+#[derive(Serialize)]
+struct StoredImportResponse {
+    success: bool,
+    data: key_store::KeyData,
+}
+
+#[derive(Debug)]
+enum DecryptError {
+    Corrupt(String),
+    WrongPassword,
+}
+
+fn derive_key(password: &str, params: &KdfParams) -> Result<[u8; DERIVED_KEY_LEN], DecryptError> {
+    if params.dklen != DERIVED_KEY_LEN {
+        return Err(DecryptError::Corrupt("Unsupported derived key length".to_string()));
+    }
+
+    // Every file this server writes uses the defaults; anything costlier would
+    // let one import tie up the server's memory or request loop.
+    if params.m_cost > DEFAULT_M_COST || params.t_cost > DEFAULT_T_COST || params.p_cost > DEFAULT_P_COST {
+        return Err(DecryptError::Corrupt("KDF parameters exceed the supported maximum".to_string()));
+    }
+
+    let salt = hex::decode(&params.salt).map_err(|_| DecryptError::Corrupt("Invalid salt".to_string()))?;
+    let argon2_params = Params::new(params.m_cost, params.t_cost, params.p_cost, Some(DERIVED_KEY_LEN))
+        .map_err(|err| DecryptError::Corrupt(format!("Invalid KDF parameters: {}", err)))?;
+
+    let mut key = [0u8; DERIVED_KEY_LEN];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, argon2_params)
+        .hash_password_into(password.as_bytes(), &salt, &mut key)
+        .map_err(|err| DecryptError::Corrupt(format!("Key derivation failed: {}", err)))?;
+
+    Ok(key)
+}
+
+fn mac(derived_key: &[u8; DERIVED_KEY_LEN], ciphertext: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(&derived_key[16..]);
+    hasher.update(ciphertext);
+    hasher.finalize().into()
+}
+
+fn encrypt(keypair: &Keypair, password: &str) -> KeystoreFile {
+    let mut salt = [0u8; 16];
+    let mut iv = [0u8; 16];
+    let mut id = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut iv);
+    rand::thread_rng().fill_bytes(&mut id);
+
+    let kdfparams = KdfParams {
+        dklen: DERIVED_KEY_LEN,
+        m_cost: DEFAULT_M_COST,
+        t_cost: DEFAULT_T_COST,
+        p_cost: DEFAULT_P_COST,
+        salt: hex::encode(salt),
+    };
+
+    let derived_key = derive_key(password, &kdfparams).expect("default KDF parameters are valid");
+
+    let mut ciphertext = keypair.secret_bytes().to_vec();
+    Aes128Ctr::new(derived_key[..16].into(), &iv.into()).apply_keystream(&mut ciphertext);
+
+    KeystoreFile {
+        version: KEYSTORE_FILE_VERSION,
+        id: hex::encode(id),
+        pubkey: keypair.pubkey().to_string(),
+        crypto: KeystoreCrypto {
+            cipher: "aes-128-ctr".to_string(),
+            cipherparams: CipherParams { iv: hex::encode(iv) },
+            mac: hex::encode(mac(&derived_key, &ciphertext)),
+            ciphertext: hex::encode(ciphertext),
+            kdf: "argon2id".to_string(),
+            kdfparams,
+        },
+    }
+}
+
+fn decrypt(file: &KeystoreFile, password: &str) -> Result<Keypair, DecryptError> {
+    let corrupt = |reason: &str| DecryptError::Corrupt(reason.to_string());
+
+    if file.version != KEYSTORE_FILE_VERSION {
+        return Err(corrupt("Unsupported keystore version"));
+    }
+    if file.crypto.cipher != "aes-128-ctr" {
+        return Err(corrupt("Unsupported cipher"));
+    }
+    if file.crypto.kdf != "argon2id" {
+        return Err(corrupt("Unsupported KDF"));
+    }
+
+    let iv: [u8; 16] = hex::decode(&file.crypto.cipherparams.iv).ok()
+        .and_then(|iv| iv.try_into().ok())
+        .ok_or_else(|| corrupt("Invalid IV"))?;
+    let mut ciphertext = hex::decode(&file.crypto.ciphertext).map_err(|_| corrupt("Invalid ciphertext"))?;
+    let expected_mac = hex::decode(&file.crypto.mac).map_err(|_| corrupt("Invalid MAC"))?;
+    if ciphertext.len() != 32 || expected_mac.len() != 32 {
+        return Err(corrupt("Invalid ciphertext or MAC length"));
+    }
+
+    let derived_key = derive_key(password, &file.crypto.kdfparams)?;
+
+    // Compare without short-circuiting so timing does not leak the MAC
+    let actual_mac = mac(&derived_key, &ciphertext);
+    if actual_mac.iter().zip(&expected_mac).fold(0u8, |acc, (a, b)| acc | (a ^ b)) != 0 {
+        return Err(DecryptError::WrongPassword);
+    }
+
+    Aes128Ctr::new(derived_key[..16].into(), &iv.into()).apply_keystream(&mut ciphertext);
+    let seed: [u8; 32] = ciphertext.try_into().unwrap();
+    let keypair = Keypair::new_from_array(seed);
+
+    // The MAC matched, so a different pubkey means the cleartext header was edited
+    if keypair.pubkey().to_string() != file.pubkey {
+        return Err(corrupt("Pubkey does not match the encrypted key"));
+    }
+
+    Ok(keypair)
+}
+
+pub fn export(store: Option<&KeyStore>, content: &str) -> JsonResponse {
+    let data: ExportRequest = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    if data.password.is_empty() {
+        return error_response(400, "Password is required");
+    }
+
+    let keypair = match (&data.secret, &data.key_id) {
+        (Some(secret), None) => match keypair::decode_secret(secret, None) {
+            Ok((kp, _)) => kp,
+            Err(error) => return error_response(400, &error),
+        },
+        // A stored key only leaves the server for someone who could open the store
+        (None, Some(key_id)) => {
+            let Some(master_passphrase) = &data.master_passphrase else {
+                return error_response(401, "masterPassphrase is required to export a stored key");
+            };
+            if store.is_some_and(|store| !store.verify_passphrase(master_passphrase)) {
+                return error_response(401, "Wrong master passphrase");
+            }
+            match key_store::lookup(store, key_id) {
+                Ok(kp) => kp,
+                Err(response) => return response,
+            }
+        }
+        _ => return error_response(400, "Provide exactly one of secret or keyId"),
+    };
+
+    let response_data = ExportResponse {
+        success: true,
+        data: encrypt(&keypair, &data.password),
+    };
+
+    json_response(200, serde_json::to_string(&response_data).unwrap())
+}
+
+pub fn import(store: Option<&mut KeyStore>, content: &str) -> JsonResponse {
+    let data: ImportRequest = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    // Accept the keystore either inline or as the raw file contents
+    let parsed = match data.keystore {
+        Value::String(raw) => serde_json::from_str::<KeystoreFile>(&raw),
+        other => serde_json::from_value::<KeystoreFile>(other),
+    };
+    let file = match parsed {
+        Ok(file) => file,
+        Err(err) => return error_response(400, &format!("Corrupt keystore file: {}", err)),
+    };
+
+    let keypair = match decrypt(&file, &data.password) {
+        Ok(kp) => kp,
+        Err(DecryptError::WrongPassword) => return error_response(401, "Wrong password"),
+        Err(DecryptError::Corrupt(reason)) => return error_response(400, &format!("Corrupt keystore file: {}", reason)),
+    };
+
+    if data.store {
+        let store = match store {
+            Some(store) => store,
+            None => return key_store::not_configured(),
+        };
+
+        return match store.insert(keypair, data.label) {
+            Ok(key) => json_response(200, serde_json::to_string(&StoredImportResponse { success: true, data: key }).unwrap()),
+            Err(error) => error_response(500, &error),
+        };
+    }
+
+    let response_data = ImportResponse {
+        success: true,
+        data: ImportData { pubkey: keypair.pubkey().to_string() },
+    };
+
+    json_response(200, serde_json::to_string(&response_data).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(m_cost: u32, t_cost: u32, p_cost: u32) -> KdfParams {
+        KdfParams { dklen: DERIVED_KEY_LEN, m_cost, t_cost, p_cost, salt: hex::encode([0u8; 16]) }
+    }
+
+    #[test]
+    fn oversized_kdf_params_are_rejected_before_hashing() {
+        for params in [
+            params(u32::MAX, DEFAULT_T_COST, DEFAULT_P_COST),
+            params(DEFAULT_M_COST, u32::MAX, DEFAULT_P_COST),
+            params(DEFAULT_M_COST, DEFAULT_T_COST, 64),
+        ] {
+            assert!(matches!(derive_key("password", &params), Err(DecryptError::Corrupt(_))));
+        }
+    }
+
+    #[test]
+    fn stored_key_export_needs_the_master_passphrase() {
+        let path = std::env::temp_dir().join(format!("keystore_file_{}.json", Keypair::new().pubkey()));
+        let mut store = KeyStore::open(path.clone(), "master").unwrap();
+        let key_id = serde_json::to_value(store.insert(Keypair::new(), None).unwrap()).unwrap()["keyId"].clone();
+
+        let status = |master: Option<&str>| {
+            let request = serde_json::json!({ "keyId": key_id, "password": "file", "masterPassphrase": master });
+            export(Some(&store), &request.to_string()).status_code().0
+        };
+        let statuses = [status(None), status(Some("wrong")), status(Some("master"))];
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(statuses, [401, 401, 200]);
+    }
+
+    #[test]
+    fn exported_file_round_trips() {
+        let keypair = Keypair::new();
+        let file = encrypt(&keypair, "password");
+        assert_eq!(decrypt(&file, "password").unwrap().pubkey(), keypair.pubkey());
+        assert!(matches!(decrypt(&file, "wrong"), Err(DecryptError::WrongPassword)));
+    }
+}
@@ -0,0 +1,144 @@
+use serde::{Serialize, Deserialize};
+use solana_address_lookup_table_interface::instruction as lookup_table_instruction;
+use solana_address_lookup_table_interface::state::LOOKUP_TABLE_MAX_ADDRESSES;
+
+use crate::{error_response, fits_in_packet, json_response, optional_pubkey, parse_pubkey, parse_request, JsonResponse, TokenData};
+
+#[derive(Deserialize)]
+struct CreateLookupTableRequest {
+    authority: String,
+    payer: String,
+    #[serde(rename = "recentSlot")]
+    recent_slot: u64,
+}
+
+#[derive(Deserialize)]
+struct ExtendLookupTableRequest {
+    #[serde(rename = "lookupTable")]
+    lookup_table: String,
+    authority: String,
+    #[serde(default)]
+    payer: Option<String>,
+    addresses: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct LookupTableAuthorityRequest {
+    #[serde(rename = "lookupTable")]
+    lookup_table: String,
+    authority: String,
+}
+
+#[derive(Deserialize)]
+struct CloseLookupTableRequest {
+    #[serde(rename = "lookupTable")]
+    lookup_table: String,
+    authority: String,
+    recipient: String,
+}
+
+#[derive(Serialize)]
+struct LookupTableResponse {
+    success: bool,
+    data: TokenData,
+}
+
+#[derive(Serialize)]
+struct CreateLookupTableResponse {
+    success: bool,
+    data: CreateLookupTableData,
+}
+
+#[derive(Serialize)]
+struct CreateLookupTableData {
+    lookup_table_address: String,
+    bump_seed: u8,
+    #[serde(flatten)]
+    instruction: TokenData,
+}
+
+fn respond<T: Serialize>(result: Result<T, String>) -> JsonResponse {
+    match result {
+        Ok(response) => json_response(200, serde_json::to_string(&response).unwrap()),
+        Err(error) => error_response(400, &error),
+    }
+}
+
+// The table address is a PDA of the authority and a recent slot, so the same
+// pair always yields the same table.
+pub fn create(content: &str) -> JsonResponse {
+    respond(parse_request(content).and_then(|data: CreateLookupTableRequest| {
+        let authority = parse_pubkey(&data.authority, "authority")?;
+        let payer = parse_pubkey(&data.payer, "payer")?;
+
+        let (_, bump_seed) = lookup_table_instruction::derive_lookup_table_address(&authority, data.recent_slot);
+        let (ix, address) = lookup_table_instruction::create_lookup_table(authority, payer, data.recent_slot);
+
+        Ok(CreateLookupTableResponse {
+            success: true,
+            data: CreateLookupTableData {
+                lookup_table_address: address.to_string(),
+                bump_seed,
+                instruction: ix.into(),
+            },
+        })
+    }))
+}
+
+pub fn extend(content: &str) -> JsonResponse {
+    respond(parse_request(content).and_then(|data: ExtendLookupTableRequest| {
+        let lookup_table = parse_pubkey(&data.lookup_table, "lookup table")?;
+        let authority = parse_pubkey(&data.authority, "authority")?;
+        let payer = optional_pubkey(data.payer.as_deref(), "payer")?;
+
+        if data.addresses.is_empty() || data.addresses.len() > LOOKUP_TABLE_MAX_ADDRESSES {
+            return Err("Addresses must contain between 1 and 256 entries".to_string());
+        }
+
+        let mut addresses = Vec::with_capacity(data.addresses.len());
+        for address in &data.addresses {
+            let pubkey = parse_pubkey(address, "table entry")?;
+            if addresses.contains(&pubkey) {
+                return Err(format!("Duplicate address: {}", address));
+            }
+            addresses.push(pubkey);
+        }
+
+        let ix = lookup_table_instruction::extend_lookup_table(lookup_table, authority, payer, addresses);
+        if !fits_in_packet(std::slice::from_ref(&ix), &payer.unwrap_or(authority)) {
+            return Err("Too many addresses for one transaction; split them across several extend calls".to_string());
+        }
+
+        Ok(LookupTableResponse { success: true, data: ix.into() })
+    }))
+}
+
+pub fn freeze(content: &str) -> JsonResponse {
+    respond(parse_request(content).and_then(|data: LookupTableAuthorityRequest| {
+        let lookup_table = parse_pubkey(&data.lookup_table, "lookup table")?;
+        let authority = parse_pubkey(&data.authority, "authority")?;
+        let ix = lookup_table_instruction::freeze_lookup_table(lookup_table, authority);
+        Ok(LookupTableResponse { success: true, data: ix.into() })
+    }))
+}
+
+pub fn deactivate(content: &str) -> JsonResponse {
+    respond(parse_request(content).and_then(|data: LookupTableAuthorityRequest| {
+        let lookup_table = parse_pubkey(&data.lookup_table, "lookup table")?;
+        let authority = parse_pubkey(&data.authority, "authority")?;
+        let ix = lookup_table_instruction::deactivate_lookup_table(lookup_table, authority);
+        Ok(LookupTableResponse { success: true, data: ix.into() })
+    }))
+}
+
+// Only a deactivated table can be closed, and only once the deactivation
+// slot has dropped out of the slot hashes sysvar (about 512 slots later).
+pub fn close(content: &str) -> JsonResponse {
+    respond(parse_request(content).and_then(|data: CloseLookupTableRequest| {
+        let lookup_table = parse_pubkey(&data.lookup_table, "lookup table")?;
+        let authority = parse_pubkey(&data.authority, "authority")?;
+        let recipient = parse_pubkey(&data.recipient, "recipient")?;
+        let ix = lookup_table_instruction::close_lookup_table(lookup_table, authority, recipient);
+        Ok(LookupTableResponse { success: true, data: ix.into() })
+    }))
+}
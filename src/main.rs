@@ -1,15 +1,43 @@
 use std::str::FromStr;
-use tiny_http::{Header, Server, Response, Method};
+use tiny_http::{Header, Server, Request, Response, Method};
 use solana_sdk::{signature::Keypair, signer::Signer, pubkey:: Pubkey};
 use spl_token::{instruction::initialize_mint};
 use serde::{Serialize, Deserialize};
-use bs58;
 use base64::{engine::general_purpose, Engine};
 use spl_token::instruction::mint_to;
 use serde_json::json;
 use solana_sdk::signature::Signature;
-use solana_sdk::system_instruction;
-
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::Message;
+use solana_packet::PACKET_DATA_SIZE;
+use solana_system_interface::instruction as system_instruction;
+use std::io::Cursor;
+use encoding::Encoding;
+
+mod account_decode;
+mod address;
+mod batch_verify;
+mod encoding;
+mod fee;
+mod idl;
+mod instruction_decode;
+mod key_store;
+mod keypair;
+mod keystore_file;
+mod lookup_table;
+mod memo;
+mod mnemonic;
+mod offchain;
+mod payout;
+mod precompile;
+mod program;
+mod raw_instruction;
+mod rent;
+mod siws;
+mod solana_pay;
+mod stake;
+
+type JsonResponse = Response<Cursor<Vec<u8>>>;
 
 #[derive(Serialize)]
 struct KeypairResponse {
@@ -25,7 +53,8 @@ struct KeypairData {
 
 #[derive(Deserialize)]
 struct CreateTokenRequest {
-    mintAuthority: String,
+    #[serde(rename = "mintAuthority")]
+    mint_authority: String,
     mint: String,
     decimals: u8,
 }
@@ -50,18 +79,77 @@ struct AccountMetaData {
     is_writable: bool,
 }
 
+impl From<Instruction> for TokenData {
+    fn from(ix: Instruction) -> Self {
+        TokenData {
+            program_id: ix.program_id.to_string(),
+            accounts: ix.accounts.iter().map(|meta| {
+                AccountMetaData {
+                    pubkey: meta.pubkey.to_string(),
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                }
+            }).collect(),
+            instruction_data: general_purpose::STANDARD.encode(ix.data),
+        }
+    }
+}
+
+impl From<Instruction> for SendSolData {
+    fn from(ix: Instruction) -> Self {
+        SendSolData {
+            program_id: ix.program_id.to_string(),
+            accounts: ix.accounts.iter().map(|meta| meta.pubkey.to_string()).collect(),
+            instruction_data: general_purpose::STANDARD.encode(ix.data),
+        }
+    }
+}
+
 #[derive(Deserialize)]
 struct MintTokenRequest {
     mint: String,
     destination: String,
     authority: String,
     amount: u64,
+    #[serde(default)]
+    memo: Option<String>,
+}
+
+#[derive(Serialize)]
+struct MintTokenResponse {
+    success: bool,
+    data: WithMemo<TokenData>,
+}
+
+// A route's main instruction, plus the memo instruction to send alongside it
+// when the request asked for one
+#[derive(Serialize)]
+struct WithMemo<T> {
+    #[serde(flatten)]
+    instruction: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memo: Option<T>,
+}
+
+impl<T: From<Instruction>> WithMemo<T> {
+    fn new(ix: Instruction, memo: Option<Instruction>) -> Self {
+        WithMemo { instruction: ix.into(), memo: memo.map(T::from) }
+    }
 }
 
 #[derive(Deserialize)]
 struct SignMessageRequest {
     message: String,
+    #[serde(default)]
     secret: String,
+    #[serde(rename = "keyId", default)]
+    key_id: Option<String>,
+    #[serde(default)]
+    offchain: bool,
+    #[serde(rename = "messageEncoding", default)]
+    message_encoding: Option<String>,
+    #[serde(rename = "signatureEncoding", default)]
+    signature_encoding: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -75,6 +163,8 @@ struct SignMessageData {
     signature: String,
     public_key: String,
     message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<&'static str>,
 }
 
 #[derive(Deserialize)]
@@ -82,6 +172,12 @@ struct VerifyMessageRequest {
     message: String,
     signature: String,
     pubkey: String,
+    #[serde(default)]
+    offchain: bool,
+    #[serde(rename = "messageEncoding", default)]
+    message_encoding: Option<String>,
+    #[serde(rename = "signatureEncoding", default)]
+    signature_encoding: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -95,6 +191,8 @@ struct VerifyMessageData {
     valid: bool,
     message: String,
     pubkey: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<&'static str>,
 }
 
 #[derive(Deserialize)]
@@ -102,12 +200,21 @@ struct SendSolRequest {
     from: String,
     to: String,
     lamports: u64,
+    #[serde(default)]
+    memo: Option<String>,
 }
 
 #[derive(Serialize)]
 struct SendSolResponse {
     success: bool,
-    data: SendSolData,
+    data: WithMemo<TokenData>,
+}
+
+// Pre-v2 `/send/sol` output with bare pubkeys, served for `?version=1`
+#[derive(Serialize)]
+struct LegacySendSolResponse {
+    success: bool,
+    data: WithMemo<SendSolData>,
 }
 
 #[derive(Serialize)]
@@ -117,15 +224,144 @@ struct SendSolData {
     instruction_data: String,
 }
 
+fn read_body(request: &mut Request) -> String {
+    let mut content = String::new();
+    let _ = request.as_reader().read_to_string(&mut content);
+    content
+}
+
+fn json_response(status: u16, body: String) -> JsonResponse {
+    Response::from_string(body)
+        .with_status_code(status)
+        .with_header(Header::from_bytes(b"Content-Type", b"application/json").unwrap())
+}
+
+fn error_response(status: u16, error: &str) -> JsonResponse {
+    json_response(status, json!({ "success": false, "error": error }).to_string())
+}
+
+// Messages default to UTF-8 and signatures to base64
+fn message_encodings(message: Option<&str>, signature: Option<&str>) -> Result<(Encoding, Encoding), String> {
+    let message_encoding = Encoding::parse_or(message, Encoding::Utf8)?;
+    let signature_encoding = Encoding::parse_or(signature, Encoding::Base64)?;
+    if signature_encoding == Encoding::Utf8 {
+        return Err("Signature encoding must be base64, base58 or hex".to_string());
+    }
+    Ok((message_encoding, signature_encoding))
+}
+
+// Shared by every route that checks a signature against a pubkey
+fn decode_signature_and_pubkey(signature: &str, encoding: Encoding, pubkey: &str) -> Result<(Signature, Pubkey), &'static str> {
+    // Decode signature
+    let signature_bytes = encoding.decode(signature).ok_or("Invalid signature encoding")?;
+
+    // Parse public key
+    let pubkey = Pubkey::from_str(pubkey).map_err(|_| "Invalid pubkey format")?;
+
+    // Parse signature bytes into Signature type
+    let signature = Signature::try_from(signature_bytes.as_slice()).map_err(|_| "Invalid signature length")?;
+
+    Ok((signature, pubkey))
+}
+
+// Parses a base58 address, naming the offending field in the error
+fn parse_pubkey(value: &str, field: &str) -> Result<Pubkey, String> {
+    Pubkey::from_str(value).map_err(|_| format!("Invalid {} address", field))
+}
+
+fn optional_pubkey(value: Option<&str>, field: &str) -> Result<Option<Pubkey>, String> {
+    value.map(|value| parse_pubkey(value, field)).transpose()
+}
+
+const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
 
+// The canonical SPL Token account of `wallet` for `mint`
+fn associated_token_address(wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[wallet.as_ref(), token_program.as_ref(), mint.as_ref()], &ASSOCIATED_TOKEN_PROGRAM_ID).0
+}
+
+// Whether a legacy transaction carrying `instructions` and paid for by
+// `payer` stays within the packet size once signed.
+fn fits_in_packet(instructions: &[Instruction], payer: &Pubkey) -> bool {
+    let message = Message::new(instructions, Some(payer));
+
+    // Compact-u16 signature count followed by one 64-byte signature per signer
+    let signatures_len = 1 + 64 * message.header.num_required_signatures as usize;
+
+    signatures_len + message.serialize().len() <= PACKET_DATA_SIZE
+}
+
+// For handlers that build their response with `?` on `String` errors
+fn parse_request<'a, T: Deserialize<'a>>(content: &'a str) -> Result<T, String> {
+    serde_json::from_str(content).map_err(|_| "Invalid JSON".to_string())
+}
 
+// Routes that changed shape keep serving the old one for `?version=1`
+fn api_version(query: &str) -> u8 {
+    query_param(query, "version").and_then(|v| v.parse().ok()).unwrap_or(2)
+}
+
+fn query_param<'a>(query: &'a str, key: &str) -> Option<&'a str> {
+    query.split('&').find_map(|pair| {
+        let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+        (k == key).then_some(v)
+    })
+}
 
 fn main() {
     let server = Server::http("0.0.0.0:8080").unwrap();
     println!("🚀 Server running at http://localhost:8080");
 
+    // The key store is only enabled when a master passphrase is provided
+    let mut key_store = match std::env::var("KEYSTORE_PASSPHRASE") {
+        Ok(passphrase) => {
+            let path = std::env::var("KEYSTORE_PATH").unwrap_or_else(|_| "keystore.json".to_string());
+            match key_store::KeyStore::open(path.into(), &passphrase) {
+                Ok(store) => Some(store),
+                Err(error) => {
+                    eprintln!("Failed to open key store: {}", error);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Err(_) => None,
+    };
+
+    let mut siws_state = siws::SiwsState::default();
+
+    // IDLs in `IDL_DIR` are registered at startup; more can be uploaded later
+    let mut idl_registry = match std::env::var("IDL_DIR") {
+        Ok(dir) => match idl::IdlRegistry::load_dir(std::path::Path::new(&dir)) {
+            Ok(registry) => registry,
+            Err(error) => {
+                eprintln!("Failed to load IDLs: {}", error);
+                std::process::exit(1);
+            }
+        },
+        Err(_) => idl::IdlRegistry::default(),
+    };
+
+    // Solana Pay transaction request templates, served under /pay/tx/{name}
+    let pay_templates = match std::env::var("PAY_TEMPLATES") {
+        Ok(path) => match solana_pay::PayTemplates::load(std::path::Path::new(&path)) {
+            Ok(templates) => templates,
+            Err(error) => {
+                eprintln!("Failed to load payment templates: {}", error);
+                std::process::exit(1);
+            }
+        },
+        Err(_) => solana_pay::PayTemplates::default(),
+    };
+
+    // Only needed for lookups that opt into live cluster data
+    let rpc_url = std::env::var("RPC_URL").ok();
+
     for mut request in server.incoming_requests() {
-        match (request.method(), request.url()) {
+        let url = request.url().to_string();
+        let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+        let api_version = api_version(query);
+
+        match (request.method(), path) {
             (&Method::Post, "/keypair") => {
                 let keypair = Keypair::new();
                 let pubkey = keypair.pubkey().to_string();
@@ -144,6 +380,57 @@ fn main() {
                 let _ = request.respond(response);
             }
 
+            (&Method::Post, "/keypair/convert") => {
+                let content = read_body(&mut request);
+                let _ = request.respond(keypair::convert(&content));
+            }
+
+            // A search can take up to two minutes, so it answers from its own thread
+            (&Method::Post, "/keypair/vanity") => {
+                let Some(slot) = keypair::SearchSlot::claim() else {
+                    let _ = request.respond(error_response(429, "A vanity search is already running"));
+                    continue;
+                };
+                let content = read_body(&mut request);
+                std::thread::spawn(move || {
+                    let _ = request.respond(keypair::vanity(&content, slot));
+                });
+            }
+
+            (&Method::Post, "/keys") => {
+                let content = read_body(&mut request);
+                let _ = request.respond(key_store::generate(key_store.as_mut(), &content));
+            }
+
+            (&Method::Get, "/keys") => {
+                let _ = request.respond(key_store::list(key_store.as_ref()));
+            }
+
+            (&Method::Post, "/keys/import") => {
+                let content = read_body(&mut request);
+                let _ = request.respond(key_store::import(key_store.as_mut(), &content));
+            }
+
+            (&Method::Post, "/keystore/export") => {
+                let content = read_body(&mut request);
+                let _ = request.respond(keystore_file::export(key_store.as_ref(), &content));
+            }
+
+            (&Method::Post, "/keystore/import") => {
+                let content = read_body(&mut request);
+                let _ = request.respond(keystore_file::import(key_store.as_mut(), &content));
+            }
+
+            (&Method::Post, "/keypair/mnemonic") => {
+                let content = read_body(&mut request);
+                let _ = request.respond(mnemonic::generate(&content));
+            }
+
+            (&Method::Post, "/keypair/mnemonic/recover") => {
+                let content = read_body(&mut request);
+                let _ = request.respond(mnemonic::recover(&content));
+            }
+
             (&Method::Post, "/token/create") => {
                 let mut content = String::new();
                 let _ = request.as_reader().read_to_string(&mut content);
@@ -152,7 +439,7 @@ fn main() {
 
                 if let Ok(data) = req_data {
                     let mint_pubkey = Pubkey::from_str(&data.mint).unwrap();
-                    let mint_authority = Pubkey::from_str(&data.mintAuthority).unwrap();
+                    let mint_authority = Pubkey::from_str(&data.mint_authority).unwrap();
 
                     let ix = initialize_mint(
                         &spl_token::id(),
@@ -162,21 +449,9 @@ fn main() {
                         data.decimals,
                     ).unwrap();
 
-                    let accounts = ix.accounts.iter().map(|meta| {
-                        AccountMetaData {
-                            pubkey: meta.pubkey.to_string(),
-                            is_signer: meta.is_signer,
-                            is_writable: meta.is_writable,
-                        }
-                    }).collect::<Vec<_>>();
-
                     let response_data = CreateTokenResponse {
                         success: true,
-                        data: TokenData {
-                            program_id: ix.program_id.to_string(),
-                            accounts,
-                            instruction_data: general_purpose::STANDARD.encode(ix.data),
-                        },
+                        data: ix.into(),
                     };
 
                     let json = serde_json::to_string(&response_data).unwrap();
@@ -205,6 +480,14 @@ fn main() {
                         let dest_pubkey = Pubkey::from_str(&data.destination).unwrap();
                         let authority_pubkey = Pubkey::from_str(&data.authority).unwrap();
 
+                        let memo_ix = match memo::attach(data.memo.as_deref()) {
+                            Ok(ix) => ix,
+                            Err(error) => {
+                                let _ = request.respond(error_response(400, error));
+                                continue;
+                            }
+                        };
+
                         // Build mint_to instruction
                         let ix = mint_to(
                             &spl_token::id(),
@@ -216,21 +499,9 @@ fn main() {
                         ).unwrap();
 
 
-                        let accounts = ix.accounts.iter().map(|meta| {
-                            AccountMetaData {
-                                pubkey: meta.pubkey.to_string(),
-                                is_signer: meta.is_signer,
-                                is_writable: meta.is_writable,
-                            }
-                        }).collect::<Vec<_>>();
-
-                        let response_data = CreateTokenResponse {
+                        let response_data = MintTokenResponse {
                             success: true,
-                            data: TokenData {
-                                program_id: ix.program_id.to_string(),
-                                accounts,
-                                instruction_data: general_purpose::STANDARD.encode(ix.data),
-                            },
+                            data: WithMemo::new(ix, memo_ix),
                         };
 
                         let json = serde_json::to_string(&response_data).unwrap();
@@ -256,7 +527,7 @@ fn main() {
                     let req_data: Result<SignMessageRequest, _> = serde_json::from_str(&content);
 
                     if let Ok(data) = req_data {
-                        if data.message.is_empty() || data.secret.is_empty() {
+                        if data.message.is_empty() || (data.secret.is_empty() && data.key_id.is_none()) {
                             let response = Response::from_string(
                                 json!({ "success": false, "error": "Missing required fields" }).to_string()
                             )
@@ -266,43 +537,94 @@ fn main() {
                             continue;
                         }
 
-                        // Decode secret key from base58
-                        let secret_bytes = match bs58::decode(&data.secret).into_vec() {
-                            Ok(bytes) => bytes,
-                            Err(_) => {
-                                let response = Response::from_string(
-                                    json!({ "success": false, "error": "Invalid secret key encoding" }).to_string()
-                                )
-                                .with_status_code(400)
-                                .with_header(Header::from_bytes(b"Content-Type", b"application/json").unwrap());
-                                let _ = request.respond(response);
+                        let keypair = if let Some(key_id) = &data.key_id {
+                            // Sign with a key held in the server-side key store
+                            match key_store::lookup(key_store.as_ref(), key_id) {
+                                Ok(kp) => kp,
+                                Err(response) => {
+                                    let _ = request.respond(response);
+                                    continue;
+                                }
+                            }
+                        } else {
+                            // Decode secret key from base58
+                            let secret_bytes = match bs58::decode(&data.secret).into_vec() {
+                                Ok(bytes) => bytes,
+                                Err(_) => {
+                                    let response = Response::from_string(
+                                        json!({ "success": false, "error": "Invalid secret key encoding" }).to_string()
+                                    )
+                                    .with_status_code(400)
+                                    .with_header(Header::from_bytes(b"Content-Type", b"application/json").unwrap());
+                                    let _ = request.respond(response);
+                                    continue;
+                                }
+                            };
+
+                            // Convert secret bytes to Keypair
+                            match Keypair::try_from(secret_bytes.as_slice()) {
+                                Ok(kp) => kp,
+                                Err(_) => {
+                                    let response = Response::from_string(
+                                        json!({ "success": false, "error": "Invalid secret key length" }).to_string()
+                                    )
+                                    .with_status_code(400)
+                                    .with_header(Header::from_bytes(b"Content-Type", b"application/json").unwrap());
+                                    let _ = request.respond(response);
+                                    continue;
+                                }
+                            }
+                        };
+
+                        let (message_encoding, signature_encoding) = match message_encodings(
+                            data.message_encoding.as_deref(),
+                            data.signature_encoding.as_deref(),
+                        ) {
+                            Ok(encodings) => encodings,
+                            Err(error) => {
+                                let _ = request.respond(error_response(400, &error));
                                 continue;
                             }
                         };
 
-                        // Convert secret bytes to Keypair
-                        let keypair = match Keypair::from_bytes(&secret_bytes) {
-                            Ok(kp) => kp,
-                            Err(_) => {
-                                let response = Response::from_string(
-                                    json!({ "success": false, "error": "Invalid secret key length" }).to_string()
-                                )
-                                .with_status_code(400)
-                                .with_header(Header::from_bytes(b"Content-Type", b"application/json").unwrap());
-                                let _ = request.respond(response);
+                        let message_bytes = match message_encoding.decode(&data.message) {
+                            Some(bytes) => bytes,
+                            None => {
+                                let _ = request.respond(error_response(400, "Invalid message encoding"));
                                 continue;
                             }
                         };
 
+                        // Wrap in the off-chain message envelope when requested
+                        let (signed_bytes, format) = if data.offchain {
+                            match offchain::envelope(&message_bytes) {
+                                Ok((bytes, format)) => (bytes, Some(format)),
+                                Err(error) => {
+                                    let _ = request.respond(error_response(400, error));
+                                    continue;
+                                }
+                            }
+                        } else {
+                            (message_bytes, None)
+                        };
+
                         // Sign the message
-                        let signature = keypair.sign_message(data.message.as_bytes());
+                        let signature = keypair.sign_message(&signed_bytes);
+                        let signature = match signature_encoding.encode(signature.as_ref()) {
+                            Ok(signature) => signature,
+                            Err(error) => {
+                                let _ = request.respond(error_response(400, error));
+                                continue;
+                            }
+                        };
 
                         let response_data = SignMessageResponse {
                             success: true,
                             data: SignMessageData {
-                                signature: base64::engine::general_purpose::STANDARD.encode(signature.as_ref()),
+                                signature,
                                 public_key: keypair.pubkey().to_string(),
                                 message: data.message,
+                                format,
                             },
                         };
 
@@ -330,50 +652,47 @@ fn main() {
                     let req_data: Result<VerifyMessageRequest, _> = serde_json::from_str(&content);
 
                     if let Ok(data) = req_data {
-                        // Decode signature from base64
-                        let signature_bytes = match base64::engine::general_purpose::STANDARD.decode(&data.signature) {
-                            Ok(bytes) => bytes,
-                            Err(_) => {
-                                let response = Response::from_string(
-                                    json!({ "success": false, "error": "Invalid signature encoding" }).to_string()
-                                )
-                                .with_status_code(400)
-                                .with_header(Header::from_bytes(b"Content-Type", b"application/json").unwrap());
-                                let _ = request.respond(response);
+                        let (message_encoding, signature_encoding) = match message_encodings(
+                            data.message_encoding.as_deref(),
+                            data.signature_encoding.as_deref(),
+                        ) {
+                            Ok(encodings) => encodings,
+                            Err(error) => {
+                                let _ = request.respond(error_response(400, &error));
                                 continue;
                             }
                         };
 
-                        // Parse public key
-                        let pubkey = match Pubkey::from_str(&data.pubkey) {
-                            Ok(pk) => pk,
-                            Err(_) => {
-                                let response = Response::from_string(
-                                    json!({ "success": false, "error": "Invalid pubkey format" }).to_string()
-                                )
-                                .with_status_code(400)
-                                .with_header(Header::from_bytes(b"Content-Type", b"application/json").unwrap());
-                                let _ = request.respond(response);
+                        let (signature, pubkey) = match decode_signature_and_pubkey(&data.signature, signature_encoding, &data.pubkey) {
+                            Ok(decoded) => decoded,
+                            Err(error) => {
+                                let _ = request.respond(error_response(400, error));
                                 continue;
                             }
                         };
 
-                        // Parse signature bytes into Signature type
-                        let signature = match Signature::try_from(signature_bytes.as_slice()) {
-                            Ok(sig) => sig,
-                            Err(_) => {
-                                let response = Response::from_string(
-                                    json!({ "success": false, "error": "Invalid signature length" }).to_string()
-                                )
-                                .with_status_code(400)
-                                .with_header(Header::from_bytes(b"Content-Type", b"application/json").unwrap());
-                                let _ = request.respond(response);
+                        let message_bytes = match message_encoding.decode(&data.message) {
+                            Some(bytes) => bytes,
+                            None => {
+                                let _ = request.respond(error_response(400, "Invalid message encoding"));
                                 continue;
                             }
                         };
 
+                        let (signed_bytes, format) = if data.offchain {
+                            match offchain::envelope(&message_bytes) {
+                                Ok((bytes, format)) => (bytes, Some(format)),
+                                Err(error) => {
+                                    let _ = request.respond(error_response(400, error));
+                                    continue;
+                                }
+                            }
+                        } else {
+                            (message_bytes, None)
+                        };
+
                         // Verify
-                        let valid = signature.verify(pubkey.as_ref(), data.message.as_bytes());
+                        let valid = signature.verify(pubkey.as_ref(), &signed_bytes);
 
                         let response_data = VerifyMessageResponse {
                             success: true,
@@ -381,6 +700,7 @@ fn main() {
                                 valid,
                                 message: data.message,
                                 pubkey: pubkey.to_string(),
+                                format,
                             },
                         };
 
@@ -401,6 +721,21 @@ fn main() {
                     }
                 }
 
+            (&Method::Post, "/message/verify/batch") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(batch_verify::verify_batch(&content));
+                }
+
+            (&Method::Post, "/auth/siws/challenge") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(siws::challenge(&mut siws_state, &content));
+                }
+
+            (&Method::Post, "/auth/siws/verify") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(siws::verify(&mut siws_state, &content));
+                }
+
             (&Method::Post, "/send/sol") => {
                     let mut content = String::new();
                     let _ = request.as_reader().read_to_string(&mut content);
@@ -445,21 +780,22 @@ fn main() {
                             continue;
                         }
 
+                        let memo_ix = match memo::attach(data.memo.as_deref()) {
+                            Ok(ix) => ix,
+                            Err(error) => {
+                                let _ = request.respond(error_response(400, error));
+                                continue;
+                            }
+                        };
+
                         // Create transfer instruction
                         let ix = system_instruction::transfer(&from_pubkey, &to_pubkey, data.lamports);
 
-                        let accounts = ix.accounts.iter().map(|meta| meta.pubkey.to_string()).collect::<Vec<_>>();
-
-                        let response_data = SendSolResponse {
-                            success: true,
-                            data: SendSolData {
-                                program_id: ix.program_id.to_string(),
-                                accounts,
-                                instruction_data: base64::engine::general_purpose::STANDARD.encode(ix.data),
-                            },
+                        let json = if api_version == 1 {
+                            serde_json::to_string(&LegacySendSolResponse { success: true, data: WithMemo::new(ix, memo_ix) }).unwrap()
+                        } else {
+                            serde_json::to_string(&SendSolResponse { success: true, data: WithMemo::new(ix, memo_ix) }).unwrap()
                         };
-
-                        let json = serde_json::to_string(&response_data).unwrap();
                         let response = Response::from_string(json)
                             .with_status_code(200)
                             .with_header(Header::from_bytes(b"Content-Type", b"application/json").unwrap());
@@ -476,6 +812,194 @@ fn main() {
                     }
                 }
 
+            (&Method::Post, "/send/sol/batch") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(payout::send_sol_batch(&content, api_version));
+                }
+
+            (&Method::Post, "/address/pda") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(address::pda(&content));
+                }
+
+            (&Method::Post, "/address/with-seed") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(address::with_seed(&content));
+                }
+
+            (&Method::Post, "/address/inspect") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(address::inspect(&content));
+                }
+
+            (&Method::Post, "/address/on-curve") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(address::on_curve(&content));
+                }
+
+            (&Method::Post, "/stake/create") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(stake::create(&content));
+                }
+
+            (&Method::Post, "/stake/delegate") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(stake::delegate(&content));
+                }
+
+            (&Method::Post, "/stake/deactivate") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(stake::deactivate(&content));
+                }
+
+            (&Method::Post, "/stake/withdraw") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(stake::withdraw(&content));
+                }
+
+            (&Method::Post, "/stake/split") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(stake::split(&content));
+                }
+
+            (&Method::Post, "/stake/merge") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(stake::merge(&content));
+                }
+
+            (&Method::Post, "/stake/authorize") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(stake::authorize(&content));
+                }
+
+            (&Method::Post, "/lookup-table/create") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(lookup_table::create(&content));
+                }
+
+            (&Method::Post, "/lookup-table/extend") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(lookup_table::extend(&content));
+                }
+
+            (&Method::Post, "/lookup-table/freeze") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(lookup_table::freeze(&content));
+                }
+
+            (&Method::Post, "/lookup-table/deactivate") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(lookup_table::deactivate(&content));
+                }
+
+            (&Method::Post, "/lookup-table/close") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(lookup_table::close(&content));
+                }
+
+            (&Method::Post, "/program/set-upgrade-authority") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(program::set_upgrade_authority(&content));
+                }
+
+            (&Method::Post, "/program/upgrade") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(program::upgrade(&content));
+                }
+
+            (&Method::Post, "/program/close") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(program::close(&content));
+                }
+
+            (&Method::Post, "/program/extend") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(program::extend(&content));
+                }
+
+            (&Method::Post, "/idl") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(idl::upload(&mut idl_registry, query, &content));
+                }
+
+            (&Method::Get, "/idl") => {
+                    let _ = request.respond(idl::list(&idl_registry));
+                }
+
+            (&Method::Post, path) if path.starts_with("/idl/") => {
+                    let content = read_body(&mut request);
+                    let response = match path["/idl/".len()..].split_once('/') {
+                        Some((program, instruction)) if !instruction.contains('/') => {
+                            idl::build_instruction(&idl_registry, program, instruction, &content)
+                        }
+                        _ => error_response(404, "Not Found"),
+                    };
+                    let _ = request.respond(response);
+                }
+
+            (&Method::Get, "/rent") => {
+                    let _ = request.respond(rent::rent(query, rpc_url.as_deref()));
+                }
+
+            (&Method::Post, "/fee/estimate") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(fee::estimate(&content));
+                }
+
+            (&Method::Post, "/pay/transfer-request") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(solana_pay::transfer_request(&content));
+                }
+
+            (&Method::Post, "/pay/parse") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(solana_pay::parse(&content));
+                }
+
+            (&Method::Get, path) if path.starts_with("/pay/tx/") => {
+                    let _ = request.respond(solana_pay::transaction_request_metadata(&pay_templates, &path["/pay/tx/".len()..]));
+                }
+
+            (&Method::Post, path) if path.starts_with("/pay/tx/") => {
+                    let content = read_body(&mut request);
+                    let name = &path["/pay/tx/".len()..];
+                    let _ = request.respond(solana_pay::transaction_request(&pay_templates, rpc_url.as_deref(), name, query, &content));
+                }
+
+            (&Method::Options, path) if path.starts_with("/pay/tx/") => {
+                    let _ = request.respond(solana_pay::with_cors(json_response(204, String::new())));
+                }
+
+            (&Method::Post, "/memo") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(memo::memo(&content));
+                }
+
+            (&Method::Post, "/account/decode") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(account_decode::decode(&content));
+                }
+
+            (&Method::Post, "/instruction/raw") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(raw_instruction::build(&content));
+                }
+
+            (&Method::Post, "/instruction/decode") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(instruction_decode::decode(&content));
+                }
+
+            (&Method::Post, "/instruction/ed25519") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(precompile::ed25519(key_store.as_ref(), &content));
+                }
+
+            (&Method::Post, "/instruction/secp256k1") => {
+                    let content = read_body(&mut request);
+                    let _ = request.respond(precompile::secp256k1(&content));
+                }
+
             _ => {
                 let response = Response::from_string("{\"success\":false,\"error\":\"Not Found\"}")
                     .with_status_code(404)
@@ -0,0 +1,78 @@
+use std::str::FromStr;
+use serde::{Serialize, Deserialize};
+use solana_sdk::{instruction::{AccountMeta, Instruction}, pubkey, pubkey::Pubkey};
+
+use crate::{error_response, json_response, JsonResponse, TokenData};
+
+// SPL Memo v2; v1 (Memo1UhkJ...) ignores signer accounts
+pub const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+// The largest memo that still fits a single-signer transaction
+const MAX_MEMO_BYTES: usize = 566;
+
+#[derive(Deserialize)]
+struct MemoRequest {
+    memo: String,
+    #[serde(default)]
+    signers: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct MemoResponse {
+    success: bool,
+    data: TokenData,
+}
+
+// Every listed signer must sign the transaction; the program fails otherwise
+pub fn instruction(memo: &str, signers: &[Pubkey]) -> Instruction {
+    Instruction {
+        program_id: MEMO_PROGRAM_ID,
+        accounts: signers.iter().map(|signer| AccountMeta::new_readonly(*signer, true)).collect(),
+        data: memo.as_bytes().to_vec(),
+    }
+}
+
+pub fn validate(memo: &str) -> Result<(), &'static str> {
+    if memo.is_empty() {
+        return Err("Memo must not be empty");
+    }
+    if memo.len() > MAX_MEMO_BYTES {
+        return Err("Memo must be at most 566 bytes");
+    }
+    Ok(())
+}
+
+// Builds the memo for the optional `memo` field on transfer routes
+pub fn attach(memo: Option<&str>) -> Result<Option<Instruction>, &'static str> {
+    memo.map(|memo| validate(memo).map(|_| instruction(memo, &[]))).transpose()
+}
+
+pub fn memo(content: &str) -> JsonResponse {
+    let data: MemoRequest = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    if let Err(error) = validate(&data.memo) {
+        return error_response(400, error);
+    }
+
+    let mut signers = Vec::with_capacity(data.signers.len());
+    for signer in &data.signers {
+        let pubkey = match Pubkey::from_str(signer) {
+            Ok(pk) => pk,
+            Err(_) => return error_response(400, &format!("Invalid signer address: {}", signer)),
+        };
+        if signers.contains(&pubkey) {
+            return error_response(400, &format!("Duplicate signer: {}", signer));
+        }
+        signers.push(pubkey);
+    }
+
+    let response_data = MemoResponse {
+        success: true,
+        data: instruction(&data.memo, &signers).into(),
+    };
+
+    json_response(200, serde_json::to_string(&response_data).unwrap())
+}
@@ -0,0 +1,141 @@
+use serde::{Serialize, Deserialize};
+use bip39::Mnemonic;
+use solana_derivation_path::DerivationPath;
+use solana_keypair::seed_derivable::keypair_from_seed_and_derivation_path;
+use solana_sdk::signer::Signer;
+
+use crate::{error_response, json_response, JsonResponse};
+
+const MAX_DERIVED_ACCOUNTS: u32 = 100;
+
+// Hardened indexes are offset by 2^31, so account indexes must stay below it
+const DERIVATION_INDEX_LIMIT: u32 = 1 << 31;
+
+#[derive(Deserialize)]
+struct GenerateMnemonicRequest {
+    #[serde(rename = "wordCount", default = "default_word_count")]
+    word_count: usize,
+    #[serde(default)]
+    passphrase: String,
+    #[serde(flatten)]
+    range: AccountRange,
+}
+
+#[derive(Deserialize)]
+struct RecoverMnemonicRequest {
+    mnemonic: String,
+    #[serde(default)]
+    passphrase: String,
+    #[serde(flatten)]
+    range: AccountRange,
+}
+
+#[derive(Deserialize)]
+struct AccountRange {
+    #[serde(rename = "startIndex", default)]
+    start_index: u32,
+    #[serde(default = "default_account_count")]
+    accounts: u32,
+}
+
+#[derive(Serialize)]
+struct MnemonicResponse {
+    success: bool,
+    data: MnemonicData,
+}
+
+#[derive(Serialize)]
+struct MnemonicData {
+    mnemonic: String,
+    accounts: Vec<DerivedAccount>,
+}
+
+#[derive(Serialize)]
+struct DerivedAccount {
+    index: u32,
+    derivation_path: String,
+    pubkey: String,
+    secret: String,
+}
+
+fn default_word_count() -> usize {
+    12
+}
+
+fn default_account_count() -> u32 {
+    1
+}
+
+pub fn generate(content: &str) -> JsonResponse {
+    // An empty body asks for the defaults
+    let content = if content.trim().is_empty() { "{}" } else { content };
+    let data: GenerateMnemonicRequest = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    if data.word_count != 12 && data.word_count != 24 {
+        return error_response(400, "Word count must be 12 or 24");
+    }
+
+    let mnemonic = match Mnemonic::generate(data.word_count) {
+        Ok(mnemonic) => mnemonic,
+        Err(_) => return error_response(500, "Failed to generate mnemonic"),
+    };
+
+    respond_with_accounts(mnemonic, &data.passphrase, &data.range)
+}
+
+pub fn recover(content: &str) -> JsonResponse {
+    let data: RecoverMnemonicRequest = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    let mnemonic = match Mnemonic::parse(data.mnemonic.trim()) {
+        Ok(mnemonic) => mnemonic,
+        Err(err) => return error_response(400, &format!("Invalid mnemonic: {}", err)),
+    };
+
+    respond_with_accounts(mnemonic, &data.passphrase, &data.range)
+}
+
+fn respond_with_accounts(mnemonic: Mnemonic, passphrase: &str, range: &AccountRange) -> JsonResponse {
+    if range.accounts == 0 || range.accounts > MAX_DERIVED_ACCOUNTS {
+        return error_response(400, "Accounts must be between 1 and 100");
+    }
+
+    let end = match range.start_index.checked_add(range.accounts) {
+        Some(end) if end <= DERIVATION_INDEX_LIMIT => end,
+        _ => return error_response(400, "Account index out of range"),
+    };
+
+    let seed = mnemonic.to_seed(passphrase);
+
+    let mut accounts = Vec::with_capacity(range.accounts as usize);
+    for index in range.start_index..end {
+        // m/44'/501'/index'/0'
+        let path = DerivationPath::new_bip44(Some(index), Some(0));
+        let keypair = match keypair_from_seed_and_derivation_path(&seed, Some(path)) {
+            Ok(kp) => kp,
+            Err(_) => return error_response(500, "Failed to derive keypair"),
+        };
+
+        accounts.push(DerivedAccount {
+            index,
+            derivation_path: format!("m/44'/501'/{}'/0'", index),
+            pubkey: keypair.pubkey().to_string(),
+            secret: bs58::encode(keypair.to_bytes()).into_string(),
+        });
+    }
+
+    let response_data = MnemonicResponse {
+        success: true,
+        data: MnemonicData {
+            mnemonic: mnemonic.to_string(),
+            accounts,
+        },
+    };
+
+    json_response(200, serde_json::to_string(&response_data).unwrap())
+}
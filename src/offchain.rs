@@ -0,0 +1,44 @@
+use solana_offchain_message::{MessageFormat, OffchainMessage};
+
+// Wraps `message` in the version 0 off-chain message envelope
+// (`\xffsolana offchain`, version, format, length) that wallets and Ledger
+// sign for `signMessage`. Returns the bytes to sign and the chosen format.
+pub fn envelope(message: &[u8]) -> Result<(Vec<u8>, &'static str), &'static str> {
+    // OffchainMessage::new gives the same error for all three problems
+    if message.is_empty() {
+        return Err("Message must not be empty");
+    }
+    if std::str::from_utf8(message).is_err() {
+        return Err("Off-chain messages must be valid UTF-8");
+    }
+    let offchain_message = OffchainMessage::new(0, message)
+        .map_err(|_| "Message is too long for an off-chain message")?;
+
+    let format = match offchain_message.get_format() {
+        MessageFormat::RestrictedAscii => "restricted-ascii",
+        MessageFormat::LimitedUtf8 => "limited-utf8",
+        MessageFormat::ExtendedUtf8 => "extended-utf8",
+    };
+
+    let bytes = offchain_message.serialize()
+        .map_err(|_| "Message is too long for an off-chain message")?;
+
+    Ok((bytes, format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_invalid_message_gets_its_own_error() {
+        assert_eq!(envelope(b"").unwrap_err(), "Message must not be empty");
+        assert_eq!(envelope(&[0xff, 0xfe]).unwrap_err(), "Off-chain messages must be valid UTF-8");
+        assert_eq!(envelope(&vec![b'a'; 70_000]).unwrap_err(), "Message is too long for an off-chain message");
+    }
+
+    #[test]
+    fn ascii_message_is_restricted_ascii() {
+        assert_eq!(envelope(b"hello").unwrap().1, "restricted-ascii");
+    }
+}
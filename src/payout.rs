@@ -0,0 +1,200 @@
+use std::str::FromStr;
+use serde::{Serialize, Deserialize};
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::instruction::Instruction;
+use solana_system_interface::instruction as system_instruction;
+
+use crate::{error_response, fits_in_packet, json_response, memo, JsonResponse, SendSolData, TokenData};
+
+// A system transfer costs 150 CU; stay below the 1.4M per-transaction cap.
+const TRANSFER_COMPUTE_UNITS: u64 = 150;
+const MAX_TRANSACTION_COMPUTE_UNITS: u64 = 1_400_000;
+
+#[derive(Deserialize)]
+struct SendSolBatchRequest {
+    from: String,
+    recipients: Vec<BatchRecipient>,
+    #[serde(default)]
+    memo: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct BatchRecipient {
+    to: String,
+    lamports: u64,
+}
+
+#[derive(Serialize)]
+struct SendSolBatchResponse<T> {
+    success: bool,
+    data: SendSolBatchData<T>,
+}
+
+#[derive(Serialize)]
+struct SendSolBatchData<T> {
+    total_lamports: u64,
+    transactions: Vec<TransferChunk<T>>,
+}
+
+#[derive(Serialize)]
+struct TransferChunk<T> {
+    recipients: Vec<usize>,
+    lamports: u64,
+    instructions: Vec<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memo: Option<T>,
+}
+
+#[derive(Serialize)]
+struct RowError {
+    index: usize,
+    error: String,
+}
+
+pub fn send_sol_batch(content: &str, api_version: u8) -> JsonResponse {
+    let data: SendSolBatchRequest = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    let from_pubkey = match Pubkey::from_str(&data.from) {
+        Ok(pk) => pk,
+        Err(_) => return error_response(400, "Invalid sender address"),
+    };
+
+    if data.recipients.is_empty() {
+        return error_response(400, "At least one recipient is required");
+    }
+
+    let memo_ix = match memo::attach(data.memo.as_deref()) {
+        Ok(memo_ix) => memo_ix,
+        Err(error) => return error_response(400, error),
+    };
+
+    // Validate every row so the caller can fix them all in one go
+    let mut transfers = Vec::with_capacity(data.recipients.len());
+    let mut errors = Vec::new();
+    for (index, recipient) in data.recipients.iter().enumerate() {
+        let to_pubkey = match Pubkey::from_str(&recipient.to) {
+            Ok(pk) => pk,
+            Err(_) => {
+                errors.push(RowError { index, error: "Invalid recipient address".to_string() });
+                continue;
+            }
+        };
+
+        if recipient.lamports == 0 {
+            errors.push(RowError { index, error: "Transfer amount must be greater than zero".to_string() });
+            continue;
+        }
+
+        transfers.push((to_pubkey, recipient.lamports));
+    }
+
+    if !errors.is_empty() {
+        return json_response(
+            400,
+            json!({ "success": false, "error": "Invalid recipients", "errors": errors }).to_string(),
+        );
+    }
+
+    let total_lamports = match transfers.iter().try_fold(0u64, |acc, (_, lamports)| acc.checked_add(*lamports)) {
+        Some(total) => total,
+        None => return error_response(400, "Total transfer amount overflows u64"),
+    };
+
+    let chunks = pack_transfers(&from_pubkey, &transfers, memo_ix.as_ref());
+
+    let json = if api_version == 1 {
+        batch_response_json::<SendSolData>(&transfers, chunks, memo_ix, total_lamports)
+    } else {
+        batch_response_json::<TokenData>(&transfers, chunks, memo_ix, total_lamports)
+    };
+
+    json_response(200, json)
+}
+
+fn batch_response_json<T: Serialize + From<Instruction>>(
+    transfers: &[(Pubkey, u64)],
+    chunks: Vec<Vec<Instruction>>,
+    memo: Option<Instruction>,
+    total_lamports: u64,
+) -> String {
+    let mut start = 0;
+    let transactions = chunks.into_iter().map(|instructions| {
+        let end = start + instructions.len();
+        let chunk = TransferChunk {
+            recipients: (start..end).collect(),
+            lamports: transfers[start..end].iter().map(|(_, lamports)| lamports).sum(),
+            instructions: instructions.into_iter().map(T::from).collect(),
+            memo: memo.clone().map(T::from),
+        };
+        start = end;
+        chunk
+    }).collect();
+
+    let response_data = SendSolBatchResponse {
+        success: true,
+        data: SendSolBatchData { total_lamports, transactions },
+    };
+
+    serde_json::to_string(&response_data).unwrap()
+}
+
+// Greedily fill each transaction with transfers until the next one would push
+// it over the packet size or compute limit. Every transaction carries the memo.
+fn pack_transfers(from: &Pubkey, transfers: &[(Pubkey, u64)], memo: Option<&Instruction>) -> Vec<Vec<Instruction>> {
+    let max_per_transaction = (MAX_TRANSACTION_COMPUTE_UNITS / TRANSFER_COMPUTE_UNITS) as usize;
+    let mut chunks = Vec::new();
+    let mut start = 0;
+
+    while start < transfers.len() {
+        // A single transfer always fits, even beside the longest memo, so
+        // every chunk makes progress
+        let mut end = start + 1;
+        while end < transfers.len()
+            && end - start < max_per_transaction
+            && fits_in_transaction(from, &transfers[start..=end], memo)
+        {
+            end += 1;
+        }
+
+        chunks.push(system_instruction::transfer_many(from, &transfers[start..end]));
+        start = end;
+    }
+
+    chunks
+}
+
+fn fits_in_transaction(from: &Pubkey, transfers: &[(Pubkey, u64)], memo: Option<&Instruction>) -> bool {
+    let mut instructions: Vec<Instruction> = memo.into_iter().cloned().collect();
+    instructions.extend(system_instruction::transfer_many(from, transfers));
+    fits_in_packet(&instructions, from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_chunk_leaves_room_for_the_memo() {
+        let from = Pubkey::new_unique();
+        let transfers: Vec<_> = (0..50).map(|_| (Pubkey::new_unique(), 1)).collect();
+        let memo_ix = memo::attach(Some(&"m".repeat(400))).unwrap().unwrap();
+
+        let with_memo = pack_transfers(&from, &transfers, Some(&memo_ix));
+        assert!(with_memo.len() > pack_transfers(&from, &transfers, None).len());
+        for chunk in &with_memo {
+            let mut instructions = vec![memo_ix.clone()];
+            instructions.extend(chunk.iter().cloned());
+            assert!(fits_in_packet(&instructions, &from));
+        }
+    }
+
+    #[test]
+    fn longest_memo_fits_with_one_transfer() {
+        let memo_ix = memo::attach(Some(&"m".repeat(566))).unwrap();
+        assert!(fits_in_transaction(&Pubkey::new_unique(), &[(Pubkey::new_unique(), 1)], memo_ix.as_ref()));
+    }
+}
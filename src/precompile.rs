@@ -0,0 +1,264 @@
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use sha3::{Digest, Keccak256};
+use solana_packet::PACKET_DATA_SIZE;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signer::Signer};
+use solana_secp256k1_program::{HASHED_PUBKEY_SERIALIZED_SIZE, SECP256K1_PRIVATE_KEY_SIZE, SIGNATURE_SERIALIZED_SIZE};
+
+use crate::encoding::Encoding;
+use crate::key_store::{self, KeyStore};
+use crate::{decode_signature_and_pubkey, error_response, fits_in_packet, json_response, keypair, message_encodings, JsonResponse, TokenData};
+
+#[derive(Deserialize)]
+struct Ed25519Request {
+    message: String,
+    #[serde(default)]
+    pubkey: Option<String>,
+    #[serde(default)]
+    signature: Option<String>,
+    #[serde(default)]
+    secret: Option<Value>,
+    #[serde(rename = "keyId", default)]
+    key_id: Option<String>,
+    #[serde(rename = "messageEncoding", default)]
+    message_encoding: Option<String>,
+    #[serde(rename = "signatureEncoding", default)]
+    signature_encoding: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Secp256k1Request {
+    message: String,
+    #[serde(default)]
+    signature: Option<String>,
+    #[serde(rename = "recoveryId", default)]
+    recovery_id: Option<u8>,
+    #[serde(rename = "ethAddress", default)]
+    eth_address: Option<String>,
+    #[serde(rename = "privateKey", default)]
+    private_key: Option<String>,
+    #[serde(rename = "messageEncoding", default)]
+    message_encoding: Option<String>,
+}
+
+#[derive(Serialize)]
+struct PrecompileResponse {
+    success: bool,
+    data: TokenData,
+}
+
+// The precompiles read the message out of the instruction itself. A message
+// over the packet size is turned away before signing; anything else is
+// measured once built, with the offsets, key and signature around it.
+fn check_message_size(message: &[u8]) -> Result<(), &'static str> {
+    if message.len() > PACKET_DATA_SIZE {
+        return Err("Message is too large to fit in a transaction");
+    }
+    Ok(())
+}
+
+// Measured in the smallest transaction that could carry it: one fee payer
+// and nothing else. The payer key only has to be the right size.
+fn check_instruction_size(ix: &Instruction) -> Result<(), &'static str> {
+    if !fits_in_packet(std::slice::from_ref(ix), &Pubkey::default()) {
+        return Err("Message is too large to fit in a transaction");
+    }
+    Ok(())
+}
+
+// EVM tooling writes hex with a `0x` prefix; accept it either way
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    hex::decode(value.strip_prefix("0x").unwrap_or(value)).ok()
+}
+
+pub fn ed25519(store: Option<&KeyStore>, content: &str) -> JsonResponse {
+    let data: Ed25519Request = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    let (message_encoding, signature_encoding) = match message_encodings(
+        data.message_encoding.as_deref(),
+        data.signature_encoding.as_deref(),
+    ) {
+        Ok(encodings) => encodings,
+        Err(error) => return error_response(400, &error),
+    };
+
+    let message = match message_encoding.decode(&data.message) {
+        Some(bytes) => bytes,
+        None => return error_response(400, "Invalid message encoding"),
+    };
+    if let Err(error) = check_message_size(&message) {
+        return error_response(400, error);
+    }
+
+    let signer = match (&data.signature, &data.secret, &data.key_id) {
+        (Some(_), None, None) => None,
+        (None, Some(secret), None) => match keypair::decode_secret(secret, None) {
+            Ok((kp, _)) => Some(kp),
+            Err(error) => return error_response(400, &error),
+        },
+        (None, None, Some(key_id)) => match key_store::lookup(store, key_id) {
+            Ok(kp) => Some(kp),
+            Err(response) => return response,
+        },
+        _ => return error_response(400, "Provide exactly one of signature, secret or keyId"),
+    };
+
+    let (signature, pubkey) = match (signer, &data.signature, &data.pubkey) {
+        (Some(kp), _, pubkey) => {
+            if pubkey.as_ref().is_some_and(|pubkey| *pubkey != kp.pubkey().to_string()) {
+                return error_response(400, "Pubkey does not match the signing key");
+            }
+            (kp.sign_message(&message), kp.pubkey())
+        }
+        (None, Some(signature), Some(pubkey)) => {
+            match decode_signature_and_pubkey(signature, signature_encoding, pubkey) {
+                Ok(decoded) => decoded,
+                Err(error) => return error_response(400, error),
+            }
+        }
+        _ => return error_response(400, "Pubkey is required with a signature"),
+    };
+
+    // The precompile fails the whole transaction on a bad signature, so catch it here
+    if !signature.verify(pubkey.as_ref(), &message) {
+        return error_response(400, "Signature does not verify for the given pubkey");
+    }
+
+    let ix = solana_ed25519_program::new_ed25519_instruction_with_signature(
+        &message,
+        signature.as_array(),
+        &pubkey.to_bytes(),
+    );
+    if let Err(error) = check_instruction_size(&ix) {
+        return error_response(400, error);
+    }
+
+    json_response(200, serde_json::to_string(&PrecompileResponse { success: true, data: ix.into() }).unwrap())
+}
+
+pub fn secp256k1(content: &str) -> JsonResponse {
+    let data: Secp256k1Request = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    let message_encoding = match Encoding::parse_or(data.message_encoding.as_deref(), Encoding::Utf8) {
+        Ok(encoding) => encoding,
+        Err(error) => return error_response(400, &error),
+    };
+
+    let message = match message_encoding.decode(&data.message) {
+        Some(bytes) => bytes,
+        None => return error_response(400, "Invalid message encoding"),
+    };
+    if let Err(error) = check_message_size(&message) {
+        return error_response(400, error);
+    }
+
+    let (signature, recovery_id) = match (&data.private_key, &data.signature) {
+        (Some(private_key), None) => {
+            let key: [u8; SECP256K1_PRIVATE_KEY_SIZE] = match decode_hex(private_key).and_then(|k| k.try_into().ok()) {
+                Some(key) => key,
+                None => return error_response(400, "Private key must be 32 hex-encoded bytes"),
+            };
+            match solana_secp256k1_program::sign_message(&key, &message) {
+                Ok(signed) => signed,
+                Err(_) => return error_response(400, "Invalid secp256k1 private key"),
+            }
+        }
+        (None, Some(signature)) => {
+            let bytes = match decode_hex(signature) {
+                Some(bytes) => bytes,
+                None => return error_response(400, "Invalid signature encoding"),
+            };
+
+            // A 65-byte signature carries its recovery id (`v`) as the last byte
+            let (rs, recovery_id) = match (bytes.len(), data.recovery_id) {
+                (SIGNATURE_SERIALIZED_SIZE, Some(id)) => (&bytes[..], id),
+                (65, None) => (&bytes[..64], bytes[64]),
+                (SIGNATURE_SERIALIZED_SIZE, None) => return error_response(400, "recoveryId is required with a 64-byte signature"),
+                _ => return error_response(400, "Signature must be 64 bytes plus recoveryId, or 65 bytes"),
+            };
+            let recovery_id = match recovery_id {
+                27 | 28 => recovery_id - 27,
+                0 | 1 => recovery_id,
+                _ => return error_response(400, "Recovery id must be 0, 1, 27 or 28"),
+            };
+
+            (rs.try_into().unwrap(), recovery_id)
+        }
+        _ => return error_response(400, "Provide exactly one of signature or privateKey"),
+    };
+
+    let recovered = match recover_eth_address(&message, &signature, recovery_id) {
+        Some(address) => address,
+        None => return error_response(400, "Signature is not a valid secp256k1 signature"),
+    };
+
+    if let Some(eth_address) = &data.eth_address {
+        let expected: [u8; HASHED_PUBKEY_SERIALIZED_SIZE] = match decode_hex(eth_address).and_then(|a| a.try_into().ok()) {
+            Some(address) => address,
+            None => return error_response(400, "Ethereum address must be 20 hex-encoded bytes"),
+        };
+        if expected != recovered {
+            return error_response(400, "Signature does not recover to the given Ethereum address");
+        }
+    } else if data.signature.is_some() {
+        return error_response(400, "ethAddress is required with a signature");
+    }
+
+    let ix = solana_secp256k1_program::new_secp256k1_instruction_with_signature(
+        &message,
+        &signature,
+        recovery_id,
+        &recovered,
+    );
+    if let Err(error) = check_instruction_size(&ix) {
+        return error_response(400, error);
+    }
+
+    json_response(200, serde_json::to_string(&PrecompileResponse { success: true, data: ix.into() }).unwrap())
+}
+
+// Mirrors the precompile's own check: keccak256 the message, recover the
+// public key and hash it down to an Ethereum address.
+fn recover_eth_address(
+    message: &[u8],
+    signature: &[u8; SIGNATURE_SERIALIZED_SIZE],
+    recovery_id: u8,
+) -> Option<[u8; HASHED_PUBKEY_SERIALIZED_SIZE]> {
+    let hash: [u8; 32] = Keccak256::digest(message).into();
+    let signature = libsecp256k1::Signature::parse_standard(signature).ok()?;
+    let recovery_id = libsecp256k1::RecoveryId::parse(recovery_id).ok()?;
+    let pubkey = libsecp256k1::recover(&libsecp256k1::Message::parse(&hash), &signature, &recovery_id).ok()?;
+    let pubkey: [u8; 64] = pubkey.serialize()[1..].try_into().unwrap();
+    Some(solana_secp256k1_program::eth_address_from_pubkey(&pubkey))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::Keypair;
+
+    fn ed25519_instruction(message_len: usize) -> Instruction {
+        let keypair = Keypair::new();
+        let message = vec![0u8; message_len];
+        let signature = keypair.sign_message(&message);
+        solana_ed25519_program::new_ed25519_instruction_with_signature(&message, signature.as_array(), &keypair.pubkey().to_bytes())
+    }
+
+    #[test]
+    fn message_near_packet_size_is_rejected_once_built() {
+        let message_len = PACKET_DATA_SIZE - 100;
+        assert!(check_message_size(&vec![0u8; message_len]).is_ok());
+        assert!(check_instruction_size(&ed25519_instruction(message_len)).is_err());
+    }
+
+    #[test]
+    fn short_message_fits() {
+        assert!(check_instruction_size(&ed25519_instruction(32)).is_ok());
+    }
+}
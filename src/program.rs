@@ -0,0 +1,160 @@
+use serde::{Serialize, Deserialize};
+use solana_loader_v3_interface::{get_program_data_address, instruction as loader_instruction};
+
+use crate::{error_response, json_response, optional_pubkey, parse_pubkey, parse_request, JsonResponse, TokenData};
+
+// Accounts can grow to at most 10 MiB
+const MAX_PERMITTED_DATA_LENGTH: u32 = 10 * 1024 * 1024;
+
+#[derive(Deserialize)]
+struct SetUpgradeAuthorityRequest {
+    program: String,
+    authority: String,
+    #[serde(rename = "newAuthority", default)]
+    new_authority: Option<String>,
+    #[serde(rename = "makeImmutable", default)]
+    make_immutable: bool,
+    #[serde(default)]
+    checked: bool,
+}
+
+#[derive(Deserialize)]
+struct UpgradeRequest {
+    program: String,
+    buffer: String,
+    authority: String,
+    spill: String,
+}
+
+#[derive(Deserialize)]
+struct CloseRequest {
+    #[serde(default)]
+    program: Option<String>,
+    #[serde(default)]
+    buffer: Option<String>,
+    authority: String,
+    recipient: String,
+}
+
+#[derive(Deserialize)]
+struct ExtendRequest {
+    program: String,
+    #[serde(rename = "additionalBytes")]
+    additional_bytes: u32,
+    #[serde(default)]
+    payer: Option<String>,
+    #[serde(default)]
+    authority: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ProgramResponse {
+    success: bool,
+    data: ProgramData,
+}
+
+#[derive(Serialize)]
+struct ProgramData {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    program_data_address: Option<String>,
+    #[serde(flatten)]
+    instruction: TokenData,
+}
+
+fn respond(result: Result<ProgramData, String>) -> JsonResponse {
+    match result {
+        Ok(data) => json_response(200, serde_json::to_string(&ProgramResponse { success: true, data }).unwrap()),
+        Err(error) => error_response(400, &error),
+    }
+}
+
+pub fn set_upgrade_authority(content: &str) -> JsonResponse {
+    respond(parse_request(content).and_then(|data: SetUpgradeAuthorityRequest| {
+        let program = parse_pubkey(&data.program, "program")?;
+        let authority = parse_pubkey(&data.authority, "authority")?;
+        let new_authority = optional_pubkey(data.new_authority.as_deref(), "new authority")?;
+
+        // Dropping the authority is irreversible, so it has to be asked for explicitly
+        let ix = match (new_authority, data.make_immutable, data.checked) {
+            (Some(new_authority), false, true) => {
+                loader_instruction::set_upgrade_authority_checked(&program, &authority, &new_authority)
+            }
+            (Some(new_authority), false, false) => {
+                loader_instruction::set_upgrade_authority(&program, &authority, Some(&new_authority))
+            }
+            (None, true, false) => loader_instruction::set_upgrade_authority(&program, &authority, None),
+            (None, true, true) => return Err("A checked authority change needs a newAuthority".to_string()),
+            _ => return Err("Provide exactly one of newAuthority or makeImmutable".to_string()),
+        };
+
+        Ok(ProgramData {
+            program_data_address: Some(get_program_data_address(&program).to_string()),
+            instruction: ix.into(),
+        })
+    }))
+}
+
+pub fn upgrade(content: &str) -> JsonResponse {
+    respond(parse_request(content).and_then(|data: UpgradeRequest| {
+        let program = parse_pubkey(&data.program, "program")?;
+        let buffer = parse_pubkey(&data.buffer, "buffer")?;
+        let authority = parse_pubkey(&data.authority, "authority")?;
+        let spill = parse_pubkey(&data.spill, "spill")?;
+
+        Ok(ProgramData {
+            program_data_address: Some(get_program_data_address(&program).to_string()),
+            instruction: loader_instruction::upgrade(&program, &buffer, &authority, &spill).into(),
+        })
+    }))
+}
+
+// Closing a program closes its ProgramData account and needs the program
+// account too; a buffer is closed on its own.
+pub fn close(content: &str) -> JsonResponse {
+    respond(parse_request(content).and_then(|data: CloseRequest| {
+        let authority = parse_pubkey(&data.authority, "authority")?;
+        let recipient = parse_pubkey(&data.recipient, "recipient")?;
+
+        match (&data.program, &data.buffer) {
+            (Some(program), None) => {
+                let program = parse_pubkey(program, "program")?;
+                let program_data = get_program_data_address(&program);
+                Ok(ProgramData {
+                    program_data_address: Some(program_data.to_string()),
+                    instruction: loader_instruction::close_any(&program_data, &recipient, Some(&authority), Some(&program)).into(),
+                })
+            }
+            (None, Some(buffer)) => {
+                let buffer = parse_pubkey(buffer, "buffer")?;
+                Ok(ProgramData {
+                    program_data_address: None,
+                    instruction: loader_instruction::close(&buffer, &recipient, &authority).into(),
+                })
+            }
+            _ => Err("Provide exactly one of program or buffer".to_string()),
+        }
+    }))
+}
+
+pub fn extend(content: &str) -> JsonResponse {
+    respond(parse_request(content).and_then(|data: ExtendRequest| {
+        let program = parse_pubkey(&data.program, "program")?;
+        let payer = optional_pubkey(data.payer.as_deref(), "payer")?;
+        let authority = optional_pubkey(data.authority.as_deref(), "authority")?;
+
+        if data.additional_bytes == 0 || data.additional_bytes > MAX_PERMITTED_DATA_LENGTH {
+            return Err("additionalBytes must be between 1 and 10485760".to_string());
+        }
+
+        // Clusters with ExtendProgramChecked enabled require the upgrade authority
+        let ix = match authority {
+            Some(authority) => loader_instruction::extend_program_checked(&program, &authority, payer.as_ref(), data.additional_bytes),
+            None => loader_instruction::extend_program(&program, payer.as_ref(), data.additional_bytes),
+        };
+
+        Ok(ProgramData {
+            program_data_address: Some(get_program_data_address(&program).to_string()),
+            instruction: ix.into(),
+        })
+    }))
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use serde::{Serialize, Deserialize};
+use serde_json::json;
+use solana_sdk::{instruction::{AccountMeta, Instruction}, pubkey::Pubkey};
+
+use crate::encoding::Encoding;
+use crate::{error_response, fits_in_packet, json_response, JsonResponse, TokenData};
+
+// A transaction can lock at most 64 accounts, the program included
+const MAX_ACCOUNTS: usize = 63;
+
+#[derive(Deserialize)]
+struct RawInstructionRequest {
+    #[serde(rename = "programId")]
+    program_id: String,
+    #[serde(default)]
+    accounts: Vec<RawAccountMeta>,
+    #[serde(default)]
+    data: String,
+    #[serde(rename = "dataEncoding", default)]
+    data_encoding: Option<String>,
+    #[serde(rename = "allowDuplicates", default)]
+    allow_duplicates: bool,
+    #[serde(default)]
+    payer: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawAccountMeta {
+    pubkey: String,
+    #[serde(rename = "isSigner", default)]
+    is_signer: bool,
+    #[serde(rename = "isWritable", default)]
+    is_writable: bool,
+}
+
+#[derive(Serialize)]
+struct RawInstructionResponse {
+    success: bool,
+    data: TokenData,
+}
+
+#[derive(Serialize)]
+struct AccountError {
+    index: usize,
+    error: String,
+}
+
+pub fn build(content: &str) -> JsonResponse {
+    let data: RawInstructionRequest = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    let program_id = match Pubkey::from_str(&data.program_id) {
+        Ok(pk) => pk,
+        Err(_) => return error_response(400, "Invalid program id"),
+    };
+
+    let encoding = match Encoding::parse_or(data.data_encoding.as_deref(), Encoding::Base64) {
+        Ok(Encoding::Utf8) => return error_response(400, "dataEncoding must be hex, base64 or base58"),
+        Ok(encoding) => encoding,
+        Err(error) => return error_response(400, &error),
+    };
+
+    // Only needed to size the transaction when no account signs
+    let payer = match data.payer.as_deref().map(Pubkey::from_str) {
+        Some(Ok(payer)) => Some(payer),
+        Some(Err(_)) => return error_response(400, "Invalid payer address"),
+        None => None,
+    };
+    let instruction_data = match encoding.decode(&data.data) {
+        Some(bytes) => bytes,
+        None => return error_response(400, "Invalid data encoding"),
+    };
+
+    if data.accounts.len() > MAX_ACCOUNTS {
+        return error_response(400, "An instruction can reference at most 63 accounts");
+    }
+
+    // Check every account so the caller sees all problems at once
+    let mut metas = Vec::with_capacity(data.accounts.len());
+    let mut seen: HashMap<Pubkey, (usize, bool, bool)> = HashMap::new();
+    let mut errors = Vec::new();
+    for (index, account) in data.accounts.iter().enumerate() {
+        let pubkey = match Pubkey::from_str(&account.pubkey) {
+            Ok(pk) => pk,
+            Err(_) => {
+                errors.push(AccountError { index, error: "Invalid account address".to_string() });
+                continue;
+            }
+        };
+
+        if pubkey == program_id && (account.is_signer || account.is_writable) {
+            errors.push(AccountError { index, error: "The program account cannot be a signer or writable".to_string() });
+        }
+
+        // Remember the request index and flags of the first listing of each key
+        match seen.get(&pubkey) {
+            Some(&(first, _, is_writable)) if is_writable != account.is_writable => {
+                errors.push(AccountError { index, error: format!("Conflicting writable flag with account {}", first) });
+            }
+            Some(&(first, is_signer, _)) if is_signer != account.is_signer => {
+                errors.push(AccountError { index, error: format!("Conflicting signer flag with account {}", first) });
+            }
+            Some(&(first, _, _)) if !data.allow_duplicates => {
+                errors.push(AccountError { index, error: format!("Duplicate of account {}", first) });
+            }
+            Some(_) => {}
+            None => {
+                seen.insert(pubkey, (index, account.is_signer, account.is_writable));
+            }
+        }
+
+        metas.push(AccountMeta { pubkey, is_signer: account.is_signer, is_writable: account.is_writable });
+    }
+
+    if !errors.is_empty() {
+        return json_response(
+            400,
+            json!({ "success": false, "error": "Invalid accounts", "errors": errors }).to_string(),
+        );
+    }
+
+    let ix = Instruction { program_id, accounts: metas, data: instruction_data };
+
+    // Without a signer in the instruction the fee payer is one more account;
+    // as in precompile, a placeholder key stands in when none is given.
+    let payer = payer
+        .or_else(|| ix.accounts.iter().find(|meta| meta.is_signer).map(|meta| meta.pubkey))
+        .unwrap_or_default();
+    if !fits_in_packet(std::slice::from_ref(&ix), &payer) {
+        return error_response(400, "Instruction is too large to fit in a transaction");
+    }
+
+    json_response(200, serde_json::to_string(&RawInstructionResponse { success: true, data: ix.into() }).unwrap())
+}
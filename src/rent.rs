@@ -0,0 +1,173 @@
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{program_pack::Pack, rent::Rent, sysvar};
+use solana_stake_interface::state::StakeStateV2;
+use solana_system_interface::MAX_PERMITTED_DATA_LENGTH;
+use spl_token_2022::extension::{AccountType, ExtensionType};
+use spl_token_2022::state::{Account, Mint, Multisig};
+
+use crate::account_decode::extension_name;
+use crate::{error_response, json_response, query_param, JsonResponse};
+
+// solana_nonce::state::State::size()
+const NONCE_ACCOUNT_SIZE: usize = 80;
+
+// Token-2022 presets: accounts created through the associated token program
+// always carry ImmutableOwner, so the account presets include it.
+const TOKEN_2022_PRESETS: &[(&str, &[ExtensionType], bool)] = &[
+    ("token_2022_mint", &[], true),
+    ("token_2022_account", &[ExtensionType::ImmutableOwner], false),
+    ("token_2022_mint_transfer_fee", &[ExtensionType::TransferFeeConfig], true),
+    ("token_2022_account_transfer_fee", &[ExtensionType::ImmutableOwner, ExtensionType::TransferFeeAmount], false),
+    ("token_2022_mint_metadata_pointer", &[ExtensionType::MetadataPointer], true),
+    ("token_2022_mint_close_authority", &[ExtensionType::MintCloseAuthority], true),
+    ("token_2022_mint_non_transferable", &[ExtensionType::NonTransferable], true),
+    ("token_2022_account_non_transferable", &[ExtensionType::ImmutableOwner, ExtensionType::NonTransferableAccount], false),
+    ("token_2022_mint_interest_bearing", &[ExtensionType::InterestBearingConfig], true),
+    ("token_2022_mint_permanent_delegate", &[ExtensionType::PermanentDelegate], true),
+    ("token_2022_mint_transfer_hook", &[ExtensionType::TransferHook], true),
+    ("token_2022_account_transfer_hook", &[ExtensionType::ImmutableOwner, ExtensionType::TransferHookAccount], false),
+];
+
+#[derive(Serialize)]
+struct RentResponse {
+    success: bool,
+    data: RentData,
+}
+
+#[derive(Serialize)]
+struct RentData {
+    source: &'static str,
+    lamports_per_byte_year: u64,
+    exemption_threshold: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    space: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lamports: Option<u64>,
+    presets: Vec<RentPreset>,
+}
+
+#[derive(Serialize)]
+struct RentPreset {
+    name: &'static str,
+    space: usize,
+    lamports: u64,
+}
+
+// `?space=N` prices one account; `?mintExtensions=` or `?accountExtensions=`
+// size a Token-2022 account from a comma-separated extension list instead.
+// `?live=true` reads the Rent sysvar from RPC_URL rather than the defaults.
+pub fn rent(query: &str, rpc_url: Option<&str>) -> JsonResponse {
+    let space = match requested_space(query) {
+        Ok(space) => space,
+        Err(error) => return error_response(400, &error),
+    };
+
+    let (rent, source) = if query_param(query, "live") == Some("true") {
+        let Some(rpc_url) = rpc_url else {
+            return error_response(400, "Live rent needs RPC_URL to be configured");
+        };
+        match fetch_rent(rpc_url) {
+            Ok(rent) => (rent, "rpc"),
+            Err(error) => return error_response(502, &error),
+        }
+    } else {
+        (Rent::default(), "default")
+    };
+
+    let mut presets = vec![
+        preset(&rent, "mint", Mint::LEN),
+        preset(&rent, "token_account", Account::LEN),
+        preset(&rent, "multisig", Multisig::LEN),
+        preset(&rent, "nonce", NONCE_ACCOUNT_SIZE),
+        preset(&rent, "stake", StakeStateV2::size_of()),
+    ];
+    for (name, extensions, is_mint) in TOKEN_2022_PRESETS {
+        // Every preset is fixed-size, so the length always computes
+        if let Ok(space) = token_2022_space(extensions, *is_mint) {
+            presets.push(preset(&rent, name, space));
+        }
+    }
+
+    let data = RentData {
+        source,
+        lamports_per_byte_year: rent.lamports_per_byte_year,
+        exemption_threshold: rent.exemption_threshold,
+        space,
+        lamports: space.map(|space| rent.minimum_balance(space)),
+        presets,
+    };
+    json_response(200, serde_json::to_string(&RentResponse { success: true, data }).unwrap())
+}
+
+fn requested_space(query: &str) -> Result<Option<usize>, String> {
+    let space = query_param(query, "space");
+    let mint_extensions = query_param(query, "mintExtensions");
+    let account_extensions = query_param(query, "accountExtensions");
+
+    match (space, mint_extensions, account_extensions) {
+        (None, None, None) => Ok(None),
+        (Some(space), None, None) => {
+            let space: usize = space.parse().map_err(|_| "space must be a non-negative integer".to_string())?;
+            // Also keeps Rent::minimum_balance clear of overflow
+            if space as u64 > MAX_PERMITTED_DATA_LENGTH {
+                return Err(format!("space must be at most {} bytes", MAX_PERMITTED_DATA_LENGTH));
+            }
+            Ok(Some(space))
+        }
+        (None, Some(names), None) => token_2022_space(&parse_extensions(names)?, true).map(Some),
+        (None, None, Some(names)) => token_2022_space(&parse_extensions(names)?, false).map(Some),
+        _ => Err("Provide only one of space, mintExtensions or accountExtensions".to_string()),
+    }
+}
+
+// Names are the camelCase ones /account/decode reports, e.g. transferFeeConfig
+fn parse_extensions(names: &str) -> Result<Vec<ExtensionType>, String> {
+    let known: Vec<ExtensionType> = (1..=u16::MAX).map_while(|value| ExtensionType::try_from(value).ok()).collect();
+    names.split(',').filter(|name| !name.is_empty()).map(|name| {
+        known.iter().copied().find(|extension_type| extension_name(*extension_type) == name)
+            .ok_or_else(|| format!("Unknown extension: {}", name))
+    }).collect()
+}
+
+fn token_2022_space(extensions: &[ExtensionType], is_mint: bool) -> Result<usize, String> {
+    let (account_type, kind) = if is_mint { (AccountType::Mint, "a mint") } else { (AccountType::Account, "an account") };
+    if let Some(other) = extensions.iter().find(|extension_type| extension_type.get_account_type() != account_type) {
+        return Err(format!("{} is not {} extension", extension_name(*other), kind));
+    }
+
+    let space = if is_mint {
+        ExtensionType::try_calculate_account_len::<Mint>(extensions)
+    } else {
+        ExtensionType::try_calculate_account_len::<Account>(extensions)
+    };
+    space.map_err(|_| "Variable-length extensions such as tokenMetadata cannot be sized up front".to_string())
+}
+
+fn fetch_rent(rpc_url: &str) -> Result<Rent, String> {
+    let account = RpcClient::new(rpc_url.to_string())
+        .get_account(&sysvar::rent::ID)
+        .map_err(|error| format!("Failed to fetch rent: {}", error))?;
+    bincode::deserialize(&account.data).map_err(|_| "Invalid rent sysvar data".to_string())
+}
+
+fn preset(rent: &Rent, name: &'static str, space: usize) -> RentPreset {
+    RentPreset { name, space, lamports: rent.minimum_balance(space) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn space_above_account_limit_is_rejected() {
+        assert!(requested_space("space=10000000000000000").is_err());
+        assert!(requested_space(&format!("space={}", MAX_PERMITTED_DATA_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn largest_account_is_priced() {
+        let space = requested_space(&format!("space={}", MAX_PERMITTED_DATA_LENGTH)).unwrap().unwrap();
+        assert!(Rent::default().minimum_balance(space) > 0);
+    }
+}
@@ -0,0 +1,355 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use rand::RngCore;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::encoding::Encoding;
+use crate::{decode_signature_and_pubkey, error_response, json_response, JsonResponse};
+
+const DEFAULT_EXPIRATION_SECONDS: i64 = 300;
+const MAX_EXPIRATION_SECONDS: i64 = 600;
+// Unused challenges live until they expire, so the store is bounded: past
+// either cap the soonest-expiring challenge makes way for the new one
+const MAX_CHALLENGES_PER_ADDRESS: usize = 5;
+const MAX_PENDING_CHALLENGES: usize = 10_000;
+
+// Issued challenges keyed by nonce. A nonce is removed once it has been used
+// or has expired, so each challenge can sign in at most once.
+#[derive(Default)]
+pub struct SiwsState {
+    challenges: HashMap<String, Challenge>,
+}
+
+struct Challenge {
+    message: String,
+    domain: String,
+    address: Pubkey,
+    expires_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct ChallengeRequest {
+    domain: String,
+    address: String,
+    #[serde(default)]
+    statement: Option<String>,
+    #[serde(default)]
+    uri: Option<String>,
+    #[serde(rename = "chainId", default)]
+    chain_id: Option<String>,
+    #[serde(default)]
+    resources: Vec<String>,
+    #[serde(rename = "expirationSeconds", default = "default_expiration_seconds")]
+    expiration_seconds: i64,
+}
+
+#[derive(Serialize)]
+struct ChallengeResponse {
+    success: bool,
+    data: ChallengeData,
+}
+
+#[derive(Serialize)]
+struct ChallengeData {
+    message: String,
+    nonce: String,
+    issued_at: String,
+    expiration_time: String,
+}
+
+#[derive(Deserialize)]
+struct VerifyRequest {
+    message: String,
+    signature: String,
+    #[serde(rename = "signatureEncoding", default)]
+    signature_encoding: Option<String>,
+    #[serde(default)]
+    domain: Option<String>,
+}
+
+#[derive(Serialize)]
+struct VerifyResponse {
+    success: bool,
+    data: VerifyData,
+}
+
+#[derive(Serialize)]
+struct VerifyData {
+    valid: bool,
+    address: String,
+    domain: String,
+    nonce: String,
+    issued_at: String,
+    expiration_time: String,
+}
+
+// The fields of a SIWS message this server needs to check
+struct ParsedMessage {
+    domain: String,
+    address: String,
+    nonce: String,
+    issued_at: String,
+    expiration_time: String,
+}
+
+fn default_expiration_seconds() -> i64 {
+    DEFAULT_EXPIRATION_SECONDS
+}
+
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn build_message(data: &ChallengeRequest, nonce: &str, issued_at: &str, expiration_time: &str) -> String {
+    let mut message = format!("{} wants you to sign in with your Solana account:\n{}", data.domain, data.address);
+
+    if let Some(statement) = &data.statement {
+        message.push_str(&format!("\n\n{}", statement));
+    }
+
+    message.push('\n');
+    if let Some(uri) = &data.uri {
+        message.push_str(&format!("\nURI: {}", uri));
+    }
+    message.push_str("\nVersion: 1");
+    if let Some(chain_id) = &data.chain_id {
+        message.push_str(&format!("\nChain ID: {}", chain_id));
+    }
+    message.push_str(&format!("\nNonce: {}", nonce));
+    message.push_str(&format!("\nIssued At: {}", issued_at));
+    message.push_str(&format!("\nExpiration Time: {}", expiration_time));
+
+    if !data.resources.is_empty() {
+        message.push_str("\nResources:");
+        for resource in &data.resources {
+            message.push_str(&format!("\n- {}", resource));
+        }
+    }
+
+    message
+}
+
+fn parse_message(message: &str) -> Option<ParsedMessage> {
+    let mut lines = message.lines();
+    let domain = lines.next()?.strip_suffix(" wants you to sign in with your Solana account:")?;
+    let address = lines.next()?;
+
+    let field = |name: &str| {
+        message.lines().find_map(|line| line.strip_prefix(name)).map(str::to_string)
+    };
+
+    Some(ParsedMessage {
+        domain: domain.to_string(),
+        address: address.to_string(),
+        nonce: field("Nonce: ")?,
+        issued_at: field("Issued At: ")?,
+        expiration_time: field("Expiration Time: ")?,
+    })
+}
+
+fn evict_soonest(challenges: &mut HashMap<String, Challenge>, matches: impl Fn(&Challenge) -> bool) {
+    let soonest = challenges.iter()
+        .filter(|(_, challenge)| matches(challenge))
+        .min_by_key(|(_, challenge)| challenge.expires_at)
+        .map(|(nonce, _)| nonce.clone());
+    if let Some(nonce) = soonest {
+        challenges.remove(&nonce);
+    }
+}
+
+pub fn challenge(state: &mut SiwsState, content: &str) -> JsonResponse {
+    let data: ChallengeRequest = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    if data.domain.is_empty() || data.domain.contains(char::is_whitespace) {
+        return error_response(400, "Invalid domain");
+    }
+
+    let address = match Pubkey::from_str(&data.address) {
+        Ok(pk) => pk,
+        Err(_) => return error_response(400, "Invalid address"),
+    };
+
+    // A newline would let the statement or resources forge extra fields
+    let mut fields = data.statement.iter().chain(&data.uri).chain(&data.chain_id).chain(&data.resources);
+    if fields.any(|value| value.contains('\n') || value.contains('\r')) {
+        return error_response(400, "Fields must not contain line breaks");
+    }
+
+    if data.expiration_seconds <= 0 || data.expiration_seconds > MAX_EXPIRATION_SECONDS {
+        return error_response(400, "Expiration must be between 1 and 600 seconds");
+    }
+
+    let now = Utc::now();
+    state.challenges.retain(|_, challenge| challenge.expires_at > now);
+    if state.challenges.values().filter(|challenge| challenge.address == address).count() >= MAX_CHALLENGES_PER_ADDRESS {
+        evict_soonest(&mut state.challenges, |challenge| challenge.address == address);
+    }
+    if state.challenges.len() >= MAX_PENDING_CHALLENGES {
+        evict_soonest(&mut state.challenges, |_| true);
+    }
+
+    let mut nonce_bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+    let nonce = bs58::encode(nonce_bytes).into_string();
+
+    let expires_at = now + Duration::seconds(data.expiration_seconds);
+    let issued_at = timestamp(now);
+    let expiration_time = timestamp(expires_at);
+    let message = build_message(&data, &nonce, &issued_at, &expiration_time);
+
+    state.challenges.insert(nonce.clone(), Challenge {
+        message: message.clone(),
+        domain: data.domain,
+        address,
+        expires_at,
+    });
+
+    let response_data = ChallengeResponse {
+        success: true,
+        data: ChallengeData { message, nonce, issued_at, expiration_time },
+    };
+
+    json_response(200, serde_json::to_string(&response_data).unwrap())
+}
+
+pub fn verify(state: &mut SiwsState, content: &str) -> JsonResponse {
+    let data: VerifyRequest = match serde_json::from_str(content) {
+        Ok(data) => data,
+        Err(_) => return error_response(400, "Invalid JSON"),
+    };
+
+    let parsed = match parse_message(&data.message) {
+        Some(parsed) => parsed,
+        None => return error_response(400, "Message is not a Sign-In With Solana message"),
+    };
+
+    let signature_encoding = match Encoding::parse_or(data.signature_encoding.as_deref(), Encoding::Base64) {
+        Ok(Encoding::Utf8) => return error_response(400, "Signature encoding must be base64, base58 or hex"),
+        Ok(encoding) => encoding,
+        Err(error) => return error_response(400, &error),
+    };
+
+    let (signature, pubkey) = match decode_signature_and_pubkey(&data.signature, signature_encoding, &parsed.address) {
+        Ok(decoded) => decoded,
+        Err(error) => return error_response(400, error),
+    };
+
+    if data.domain.as_ref().is_some_and(|domain| *domain != parsed.domain) {
+        return error_response(401, "Domain mismatch");
+    }
+
+    let challenge = match state.challenges.get(&parsed.nonce) {
+        Some(challenge) => challenge,
+        None => return error_response(401, "Unknown or already used nonce"),
+    };
+
+    if challenge.expires_at <= Utc::now() {
+        state.challenges.remove(&parsed.nonce);
+        return error_response(401, "Challenge expired");
+    }
+
+    // Everything the server issued must come back unchanged
+    if challenge.domain != parsed.domain {
+        return error_response(401, "Domain mismatch");
+    }
+    if challenge.address != pubkey {
+        return error_response(401, "Address mismatch");
+    }
+    if challenge.message != data.message {
+        return error_response(401, "Message does not match the issued challenge");
+    }
+
+    if !signature.verify(pubkey.as_ref(), data.message.as_bytes()) {
+        return error_response(401, "Invalid signature");
+    }
+
+    state.challenges.remove(&parsed.nonce);
+
+    let response_data = VerifyResponse {
+        success: true,
+        data: VerifyData {
+            valid: true,
+            address: parsed.address,
+            domain: parsed.domain,
+            nonce: parsed.nonce,
+            issued_at: parsed.issued_at,
+            expiration_time: parsed.expiration_time,
+        },
+    };
+
+    json_response(200, serde_json::to_string(&response_data).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> String {
+        serde_json::json!({ "domain": "example.com", "address": Pubkey::new_unique().to_string() }).to_string()
+    }
+
+    fn pending(address: Pubkey, expires_at: DateTime<Utc>) -> Challenge {
+        Challenge { message: String::new(), domain: String::new(), address, expires_at }
+    }
+
+    // Entry "0" expires first; each later index lives a second longer
+    fn full_state(expires_at: DateTime<Utc>) -> SiwsState {
+        let mut state = SiwsState::default();
+        for index in 0..MAX_PENDING_CHALLENGES {
+            let offset = Duration::seconds(index as i64);
+            state.challenges.insert(index.to_string(), pending(Pubkey::new_unique(), expires_at + offset));
+        }
+        state
+    }
+
+    #[test]
+    fn pending_challenges_are_capped() {
+        let mut state = full_state(Utc::now() + Duration::seconds(60));
+
+        assert_eq!(challenge(&mut state, &request()).status_code().0, 200);
+        assert_eq!(state.challenges.len(), MAX_PENDING_CHALLENGES);
+        assert!(!state.challenges.contains_key("0"));
+        assert!(state.challenges.contains_key("1"));
+    }
+
+    #[test]
+    fn each_address_keeps_its_newest_challenges() {
+        let address = Pubkey::new_unique();
+        let mut state = SiwsState::default();
+        for index in 0..MAX_CHALLENGES_PER_ADDRESS {
+            let expires_at = Utc::now() + Duration::seconds(60 + index as i64);
+            state.challenges.insert(index.to_string(), pending(address, expires_at));
+        }
+        let content = serde_json::json!({ "domain": "example.com", "address": address.to_string() }).to_string();
+
+        assert_eq!(challenge(&mut state, &content).status_code().0, 200);
+        assert_eq!(state.challenges.len(), MAX_CHALLENGES_PER_ADDRESS);
+        assert!(!state.challenges.contains_key("0"));
+        assert_eq!(challenge(&mut state, &request()).status_code().0, 200);
+        assert_eq!(state.challenges.len(), MAX_CHALLENGES_PER_ADDRESS + 1);
+    }
+
+    #[test]
+    fn expiration_is_limited_to_minutes() {
+        let content = serde_json::json!({
+            "domain": "example.com",
+            "address": Pubkey::new_unique().to_string(),
+            "expirationSeconds": MAX_EXPIRATION_SECONDS + 1,
+        }).to_string();
+
+        assert_eq!(challenge(&mut SiwsState::default(), &content).status_code().0, 400);
+    }
+
+    #[test]
+    fn expired_challenges_make_room() {
+        let mut state = full_state(Utc::now() - Duration::seconds(MAX_PENDING_CHALLENGES as i64));
+
+        assert_eq!(challenge(&mut state, &request()).status_code().0, 200);
+        assert_eq!(state.challenges.len(), 1);
+    }
+}
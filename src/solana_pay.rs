@@ -0,0 +1,551 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use base64::{engine::general_purpose, Engine};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use qrcode::{render::svg, Color, QrCode};
+use serde::{Serialize, Deserialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{instruction::{AccountMeta, Instruction}, message::Message, pubkey::Pubkey, transaction::Transaction};
+use solana_system_interface::instruction as system_instruction;
+use tiny_http::Header;
+
+use crate::{associated_token_address, error_response, fits_in_packet, json_response, memo, optional_pubkey, parse_pubkey, parse_request, JsonResponse};
+
+// Text fields keep only the RFC 3986 unreserved characters unescaped
+const COMPONENT: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+const SOL_DECIMALS: u8 = 9;
+const DEFAULT_QR_SIZE: u32 = 256;
+const MIN_QR_SIZE: u32 = 64;
+const MAX_QR_SIZE: u32 = 2048;
+// Modules of blank margin the QR spec requires around the code
+const QR_QUIET_ZONE: usize = 4;
+
+#[derive(Deserialize)]
+struct TransferRequestRequest {
+    recipient: String,
+    #[serde(default)]
+    amount: Option<String>,
+    #[serde(rename = "splToken", default)]
+    spl_token: Option<String>,
+    #[serde(default)]
+    decimals: Option<u8>,
+    #[serde(default)]
+    references: Vec<String>,
+    #[serde(default)]
+    label: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    memo: Option<String>,
+    #[serde(rename = "qrFormat", default)]
+    qr_format: Option<String>,
+    #[serde(rename = "qrSize", default)]
+    qr_size: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct ParseRequest {
+    url: String,
+}
+
+#[derive(Serialize)]
+struct PayUrlResponse {
+    success: bool,
+    data: PayUrlData,
+}
+
+#[derive(Serialize)]
+struct PayUrlData {
+    url: String,
+    qr_format: &'static str,
+    qr_code: String,
+}
+
+#[derive(Serialize)]
+struct ParseResponse {
+    success: bool,
+    data: ParsedUrl,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum ParsedUrl {
+    Transfer(TransferRequest),
+    Transaction { link: String },
+}
+
+#[derive(Serialize, Default)]
+struct TransferRequest {
+    recipient: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    amount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    spl_token: Option<String>,
+    references: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memo: Option<String>,
+}
+
+impl TransferRequest {
+    // Fields must already be validated; only the text fields are encoded
+    fn to_url(&self) -> String {
+        let mut params = Vec::new();
+        if let Some(amount) = &self.amount {
+            params.push(format!("amount={}", amount));
+        }
+        if let Some(spl_token) = &self.spl_token {
+            params.push(format!("spl-token={}", spl_token));
+        }
+        for reference in &self.references {
+            params.push(format!("reference={}", reference));
+        }
+        for (key, value) in [("label", &self.label), ("message", &self.message), ("memo", &self.memo)] {
+            if let Some(value) = value {
+                params.push(format!("{}={}", key, utf8_percent_encode(value, COMPONENT)));
+            }
+        }
+
+        if params.is_empty() {
+            format!("solana:{}", self.recipient)
+        } else {
+            format!("solana:{}?{}", self.recipient, params.join("&"))
+        }
+    }
+}
+
+pub fn transfer_request(content: &str) -> JsonResponse {
+    match build_transfer_request(content) {
+        Ok(data) => json_response(200, serde_json::to_string(&PayUrlResponse { success: true, data }).unwrap()),
+        Err(error) => error_response(400, &error),
+    }
+}
+
+fn build_transfer_request(content: &str) -> Result<PayUrlData, String> {
+    let data: TransferRequestRequest = parse_request(content)?;
+
+    let recipient = parse_pubkey(&data.recipient, "recipient")?;
+    let spl_token = optional_pubkey(data.spl_token.as_deref(), "spl-token mint")?;
+    let references = data.references.iter()
+        .map(|reference| parse_pubkey(reference, "reference"))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Token decimals are only known when the caller passes them
+    if let Some(amount) = &data.amount {
+        let decimals = if spl_token.is_some() { data.decimals } else { Some(SOL_DECIMALS) };
+        validate_amount(amount, decimals)?;
+    }
+    if let Some(memo) = &data.memo {
+        memo::validate(memo)?;
+    }
+
+    let request = TransferRequest {
+        recipient: recipient.to_string(),
+        amount: data.amount,
+        spl_token: spl_token.map(|mint| mint.to_string()),
+        references: references.iter().map(Pubkey::to_string).collect(),
+        label: data.label,
+        message: data.message,
+        memo: data.memo,
+    };
+
+    let url = request.to_url();
+    let (qr_format, qr_code) = qr_code(&url, data.qr_format.as_deref(), data.qr_size)?;
+    Ok(PayUrlData { url, qr_format, qr_code })
+}
+
+pub fn parse(content: &str) -> JsonResponse {
+    let parsed = parse_request(content).and_then(|data: ParseRequest| parse_url(&data.url));
+    match parsed {
+        Ok(data) => json_response(200, serde_json::to_string(&ParseResponse { success: true, data }).unwrap()),
+        Err(error) => error_response(400, &error),
+    }
+}
+
+// A `solana:` URL is either a transfer request (`solana:<recipient>?...`) or
+// a transaction request whose path is an https link, percent-encoded when it
+// carries its own query string.
+fn parse_url(url: &str) -> Result<ParsedUrl, String> {
+    let rest = url.strip_prefix("solana:").ok_or_else(|| "URL must start with solana:".to_string())?;
+
+    if rest.starts_with("https") {
+        let link = decode_component(rest)?;
+        if !link.starts_with("https://") || link.len() == "https://".len() {
+            return Err("Transaction request link must be an https URL".to_string());
+        }
+        return Ok(ParsedUrl::Transaction { link });
+    }
+
+    let (recipient, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let mut request = TransferRequest {
+        recipient: parse_pubkey(recipient, "recipient")?.to_string(),
+        ..TransferRequest::default()
+    };
+
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = decode_component(value)?;
+        let slot = match key {
+            "amount" => &mut request.amount,
+            "spl-token" => &mut request.spl_token,
+            "label" => &mut request.label,
+            "message" => &mut request.message,
+            "memo" => &mut request.memo,
+            "reference" => {
+                request.references.push(parse_pubkey(&value, "reference")?.to_string());
+                continue;
+            }
+            // Wallets ignore parameters they do not know
+            _ => continue,
+        };
+        if slot.replace(value).is_some() {
+            return Err(format!("Duplicate {} parameter", key));
+        }
+    }
+
+    if let Some(spl_token) = &request.spl_token {
+        parse_pubkey(spl_token, "spl-token mint")?;
+    }
+    if let Some(amount) = &request.amount {
+        validate_amount(amount, request.spl_token.is_none().then_some(SOL_DECIMALS))?;
+    }
+    if let Some(memo) = &request.memo {
+        memo::validate(memo)?;
+    }
+
+    Ok(ParsedUrl::Transfer(request))
+}
+
+// Amounts are user-facing decimals ("1.5" SOL, not lamports), with a leading
+// zero below one and no exponent
+fn validate_amount(amount: &str, max_decimals: Option<u8>) -> Result<(), String> {
+    let (whole, fraction) = match amount.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (amount, None),
+    };
+
+    let is_digits = |part: &str| !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_digit());
+    if !is_digits(whole) || !fraction.is_none_or(is_digits) {
+        return Err(format!("Invalid amount: {}", amount));
+    }
+
+    match (fraction, max_decimals) {
+        (Some(fraction), Some(max)) if fraction.len() > usize::from(max) => {
+            Err(format!("Amount has more than {} decimal places", max))
+        }
+        _ => Ok(()),
+    }
+}
+
+// URLSearchParams turns `+` into a space, so wallets' URLs decode the same way here
+fn decode_component(value: &str) -> Result<String, String> {
+    percent_decode_str(&value.replace('+', " "))
+        .decode_utf8()
+        .map(|value| value.into_owned())
+        .map_err(|_| "URL is not valid UTF-8".to_string())
+}
+
+// Renders `data` as an SVG document or a base64 PNG, `size` pixels square
+pub fn qr_code(data: &str, format: Option<&str>, size: Option<u32>) -> Result<(&'static str, String), String> {
+    let size = size.unwrap_or(DEFAULT_QR_SIZE);
+    if !(MIN_QR_SIZE..=MAX_QR_SIZE).contains(&size) {
+        return Err(format!("qrSize must be between {} and {}", MIN_QR_SIZE, MAX_QR_SIZE));
+    }
+
+    let code = QrCode::new(data.as_bytes()).map_err(|_| "URL is too long for a QR code".to_string())?;
+    match format.unwrap_or("svg") {
+        "svg" => Ok(("svg", code.render::<svg::Color>().min_dimensions(size, size).build())),
+        "png" => Ok(("png", general_purpose::STANDARD.encode(render_png(&code, size)))),
+        other => Err(format!("Unsupported qrFormat: {}", other)),
+    }
+}
+
+// Scales each module to whole pixels so the code stays sharp
+fn render_png(code: &QrCode, size: u32) -> Vec<u8> {
+    let modules = code.width() + 2 * QR_QUIET_ZONE;
+    let scale = (size as usize / modules).max(1);
+    let pixels = modules * scale;
+    let colors = code.to_colors();
+
+    let mut image = vec![255u8; pixels * pixels];
+    for (index, color) in colors.iter().enumerate() {
+        if *color == Color::Light {
+            continue;
+        }
+        let x = (index % code.width() + QR_QUIET_ZONE) * scale;
+        let y = (index / code.width() + QR_QUIET_ZONE) * scale;
+        for row in y..y + scale {
+            image[row * pixels + x..row * pixels + x + scale].fill(0);
+        }
+    }
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, pixels as u32, pixels as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    // Writing to a Vec cannot fail
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&image).unwrap();
+    writer.finish().unwrap();
+    png
+}
+
+// Merchant templates for transaction requests, keyed by the name used in
+// `/pay/tx/{name}`. Each one pays a fixed amount (lamports, or token base
+// units with splToken) from the requesting wallet to the recipient.
+#[derive(Default)]
+pub struct PayTemplates {
+    templates: HashMap<String, PayTemplate>,
+}
+
+#[derive(Deserialize)]
+struct PayTemplateConfig {
+    label: String,
+    icon: String,
+    #[serde(default)]
+    message: Option<String>,
+    recipient: String,
+    amount: u64,
+    #[serde(rename = "splToken", default)]
+    spl_token: Option<String>,
+    #[serde(default)]
+    decimals: Option<u8>,
+    #[serde(rename = "tokenProgram", default)]
+    token_program: Option<String>,
+    #[serde(default)]
+    memo: Option<String>,
+    #[serde(default)]
+    references: Vec<String>,
+}
+
+// A token payment: the mint, its decimals and the program that owns the mint
+struct PayToken {
+    mint: Pubkey,
+    decimals: u8,
+    program: Pubkey,
+}
+
+struct PayTemplate {
+    label: String,
+    icon: String,
+    message: Option<String>,
+    recipient: Pubkey,
+    amount: u64,
+    token: Option<PayToken>,
+    memo: Option<String>,
+    references: Vec<Pubkey>,
+}
+
+#[derive(Deserialize)]
+struct TransactionRequestBody {
+    account: String,
+}
+
+// Transaction request responses follow the Solana Pay spec rather than the
+// `{ success, data }` envelope, since wallets read them directly
+#[derive(Serialize)]
+struct TransactionRequestMetadata<'a> {
+    label: &'a str,
+    icon: &'a str,
+}
+
+#[derive(Serialize)]
+struct TransactionRequestResponse<'a> {
+    transaction: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<&'a str>,
+}
+
+impl PayTemplates {
+    // The file maps template names to their configuration
+    pub fn load(path: &Path) -> Result<PayTemplates, String> {
+        let contents = fs::read_to_string(path).map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+        let configs: HashMap<String, PayTemplateConfig> = serde_json::from_str(&contents)
+            .map_err(|err| format!("{} is not a valid template file: {}", path.display(), err))?;
+
+        let mut templates = HashMap::new();
+        for (name, config) in configs {
+            let template = PayTemplate::parse(config).map_err(|err| format!("Template {}: {}", name, err))?;
+            templates.insert(name, template);
+        }
+        Ok(PayTemplates { templates })
+    }
+
+    // `name` comes straight from the request path, still percent-encoded
+    fn find(&self, name: &str) -> Option<&PayTemplate> {
+        decode_component(name).ok().and_then(|name| self.templates.get(&name))
+    }
+}
+
+impl PayTemplate {
+    fn parse(config: PayTemplateConfig) -> Result<PayTemplate, String> {
+        if config.amount == 0 {
+            return Err("amount must be greater than zero".to_string());
+        }
+        if let Some(memo) = &config.memo {
+            memo::validate(memo)?;
+        }
+
+        let program = match optional_pubkey(config.token_program.as_deref(), "token program")? {
+            None => spl_token::ID,
+            Some(program) if program == spl_token::ID || program == spl_token_2022::ID => program,
+            Some(_) => return Err("tokenProgram must be the token or Token-2022 program".to_string()),
+        };
+        let token = match (optional_pubkey(config.spl_token.as_deref(), "spl-token mint")?, config.decimals) {
+            (Some(mint), Some(decimals)) => Some(PayToken { mint, decimals, program }),
+            (Some(_), None) => return Err("splToken needs decimals".to_string()),
+            (None, _) if config.token_program.is_some() => return Err("tokenProgram needs splToken".to_string()),
+            (None, _) => None,
+        };
+
+        Ok(PayTemplate {
+            label: config.label,
+            icon: config.icon,
+            message: config.message,
+            recipient: parse_pubkey(&config.recipient, "recipient")?,
+            amount: config.amount,
+            token,
+            memo: config.memo,
+            references: config.references.iter()
+                .map(|reference| parse_pubkey(reference, "reference"))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    // Memo first, then the transfer with the reference keys appended as
+    // read-only accounts so the merchant can find the payment on chain
+    fn instructions(&self, account: &Pubkey, references: &[Pubkey]) -> Result<Vec<Instruction>, String> {
+        let mut transfer = match &self.token {
+            // Token-2022's builder accepts either token program
+            Some(token) => spl_token_2022::instruction::transfer_checked(
+                &token.program,
+                &associated_token_address(account, &token.mint, &token.program),
+                &token.mint,
+                &associated_token_address(&self.recipient, &token.mint, &token.program),
+                account,
+                &[],
+                self.amount,
+                token.decimals,
+            ).map_err(|error| format!("Failed to build transfer: {}", error))?,
+            None => system_instruction::transfer(account, &self.recipient, self.amount),
+        };
+        for reference in self.references.iter().chain(references) {
+            transfer.accounts.push(AccountMeta::new_readonly(*reference, false));
+        }
+
+        let mut instructions: Vec<Instruction> = self.memo.iter().map(|memo| memo::instruction(memo, &[])).collect();
+        instructions.push(transfer);
+        Ok(instructions)
+    }
+}
+
+pub fn transaction_request_metadata(templates: &PayTemplates, name: &str) -> JsonResponse {
+    match templates.find(name) {
+        Some(template) => with_cors(json_response(200, serde_json::to_string(&TransactionRequestMetadata {
+            label: &template.label,
+            icon: &template.icon,
+        }).unwrap())),
+        None => with_cors(error_response(404, "Unknown payment template")),
+    }
+}
+
+// Extra `reference` query parameters on the request link are added to the
+// template's, so each checkout can be tracked with its own key
+pub fn transaction_request(templates: &PayTemplates, rpc_url: Option<&str>, name: &str, query: &str, content: &str) -> JsonResponse {
+    let Some(template) = templates.find(name) else {
+        return with_cors(error_response(404, "Unknown payment template"));
+    };
+
+    let built = parse_request(content).and_then(|body: TransactionRequestBody| {
+        let account = parse_pubkey(&body.account, "account")?;
+        let references = query.split('&')
+            .filter_map(|pair| pair.strip_prefix("reference="))
+            .map(|reference| parse_pubkey(&decode_component(reference)?, "reference"))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let instructions = template.instructions(&account, &references)?;
+        if !fits_in_packet(&instructions, &account) {
+            return Err("Transaction is too large; use fewer references".to_string());
+        }
+        Ok((account, instructions))
+    });
+    let (account, instructions) = match built {
+        Ok(built) => built,
+        Err(error) => return with_cors(error_response(400, &error)),
+    };
+
+    // The wallet signs as fee payer, so the transaction needs a live blockhash
+    let Some(rpc_url) = rpc_url else {
+        return with_cors(error_response(400, "Transaction requests need RPC_URL to be configured"));
+    };
+    let blockhash = match RpcClient::new(rpc_url.to_string()).get_latest_blockhash() {
+        Ok(blockhash) => blockhash,
+        Err(error) => return with_cors(error_response(502, &format!("Failed to fetch blockhash: {}", error))),
+    };
+
+    let message = Message::new_with_blockhash(&instructions, Some(&account), &blockhash);
+    let transaction = bincode::serialize(&Transaction::new_unsigned(message)).unwrap();
+    with_cors(json_response(200, serde_json::to_string(&TransactionRequestResponse {
+        transaction: general_purpose::STANDARD.encode(transaction),
+        message: template.message.as_deref(),
+    }).unwrap()))
+}
+
+// Browser wallets call these routes cross-origin
+pub fn with_cors(response: JsonResponse) -> JsonResponse {
+    response
+        .with_header(Header::from_bytes(b"Access-Control-Allow-Origin", b"*").unwrap())
+        .with_header(Header::from_bytes(b"Access-Control-Allow-Methods", b"GET, POST, OPTIONS").unwrap())
+        .with_header(Header::from_bytes(b"Access-Control-Allow-Headers", b"Content-Type").unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(token_program: Option<Pubkey>) -> PayTemplateConfig {
+        PayTemplateConfig {
+            label: "Coffee".to_string(),
+            icon: "https://example.com/icon.svg".to_string(),
+            message: None,
+            recipient: Pubkey::new_unique().to_string(),
+            amount: 1_000,
+            spl_token: Some(Pubkey::new_unique().to_string()),
+            decimals: Some(6),
+            token_program: token_program.map(|program| program.to_string()),
+            memo: None,
+            references: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn token_2022_template_uses_its_program_and_accounts() {
+        let template = PayTemplate::parse(config(Some(spl_token_2022::ID))).unwrap();
+        let account = Pubkey::new_unique();
+        let instructions = template.instructions(&account, &[]).unwrap();
+        let token = template.token.as_ref().unwrap();
+
+        assert_eq!(instructions[0].program_id, spl_token_2022::ID);
+        assert_eq!(instructions[0].accounts[0].pubkey, associated_token_address(&account, &token.mint, &spl_token_2022::ID));
+    }
+
+    #[test]
+    fn token_program_defaults_to_spl_token_and_rejects_others() {
+        let template = PayTemplate::parse(config(None)).unwrap();
+        assert_eq!(template.instructions(&Pubkey::new_unique(), &[]).unwrap()[0].program_id, spl_token::ID);
+        assert!(PayTemplate::parse(config(Some(Pubkey::new_unique()))).is_err());
+    }
+
+    #[test]
+    fn template_names_are_percent_decoded() {
+        let templates = PayTemplates {
+            templates: HashMap::from([("iced coffee".to_string(), PayTemplate::parse(config(None)).unwrap())]),
+        };
+        assert!(templates.find("iced%20coffee").is_some());
+    }
+}
@@ -0,0 +1,269 @@
+use serde::{Serialize, Deserialize};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, rent::Rent};
+use solana_stake_interface::instruction as stake_instruction;
+use solana_stake_interface::state::{Authorized, Lockup, StakeAuthorize, StakeStateV2};
+
+use crate::{error_response, json_response, optional_pubkey, parse_pubkey, parse_request, JsonResponse, TokenData};
+
+// The stake program's minimum delegation is 1 lamport until the cluster
+// activates the 1 SOL minimum; this server assumes the current mainnet value.
+const MINIMUM_DELEGATION_LAMPORTS: u64 = 1;
+
+#[derive(Deserialize)]
+struct CreateStakeRequest {
+    from: String,
+    #[serde(default)]
+    stake: Option<String>,
+    #[serde(default)]
+    base: Option<String>,
+    #[serde(default)]
+    seed: Option<String>,
+    lamports: u64,
+    staker: String,
+    withdrawer: String,
+    #[serde(default)]
+    lockup: Option<LockupRequest>,
+    #[serde(default)]
+    vote: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LockupRequest {
+    #[serde(rename = "unixTimestamp", default)]
+    unix_timestamp: i64,
+    #[serde(default)]
+    epoch: u64,
+    #[serde(default)]
+    custodian: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct DelegateRequest {
+    stake: String,
+    staker: String,
+    vote: String,
+}
+
+#[derive(Deserialize)]
+struct DeactivateRequest {
+    stake: String,
+    staker: String,
+}
+
+#[derive(Deserialize)]
+struct WithdrawRequest {
+    stake: String,
+    withdrawer: String,
+    to: String,
+    lamports: u64,
+    #[serde(default)]
+    custodian: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SplitRequest {
+    stake: String,
+    staker: String,
+    #[serde(rename = "splitStake")]
+    split_stake: String,
+    lamports: u64,
+}
+
+#[derive(Deserialize)]
+struct MergeRequest {
+    destination: String,
+    source: String,
+    staker: String,
+}
+
+#[derive(Deserialize)]
+struct AuthorizeRequest {
+    stake: String,
+    authority: String,
+    #[serde(rename = "newAuthority")]
+    new_authority: String,
+    #[serde(rename = "authorizeType")]
+    authorize_type: String,
+    #[serde(default)]
+    custodian: Option<String>,
+    #[serde(default)]
+    checked: bool,
+}
+
+#[derive(Serialize)]
+struct StakeResponse {
+    success: bool,
+    data: StakeData,
+}
+
+#[derive(Serialize)]
+struct StakeData {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stake_account: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rent_exempt_reserve: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    minimum_delegation: Option<u64>,
+    instructions: Vec<TokenData>,
+}
+
+impl From<Vec<Instruction>> for StakeData {
+    fn from(instructions: Vec<Instruction>) -> Self {
+        StakeData {
+            stake_account: None,
+            rent_exempt_reserve: None,
+            minimum_delegation: None,
+            instructions: instructions.into_iter().map(TokenData::from).collect(),
+        }
+    }
+}
+
+fn rent_exempt_reserve() -> u64 {
+    Rent::default().minimum_balance(StakeStateV2::size_of())
+}
+
+// Each route builds its data with `?` and is turned into a response here
+fn respond(result: Result<StakeData, String>) -> JsonResponse {
+    match result {
+        Ok(data) => json_response(200, serde_json::to_string(&StakeResponse { success: true, data }).unwrap()),
+        Err(error) => error_response(400, &error),
+    }
+}
+
+pub fn create(content: &str) -> JsonResponse {
+    respond(build_create(content))
+}
+
+fn build_create(content: &str) -> Result<StakeData, String> {
+    let data: CreateStakeRequest = parse_request(content)?;
+
+    let from = parse_pubkey(&data.from, "sender")?;
+    let authorized = Authorized {
+        staker: parse_pubkey(&data.staker, "staker")?,
+        withdrawer: parse_pubkey(&data.withdrawer, "withdrawer")?,
+    };
+    let vote = optional_pubkey(data.vote.as_deref(), "vote account")?;
+
+    let lockup = match &data.lockup {
+        Some(lockup) => Lockup {
+            unix_timestamp: lockup.unix_timestamp,
+            epoch: lockup.epoch,
+            custodian: optional_pubkey(lockup.custodian.as_deref(), "custodian")?.unwrap_or_default(),
+        },
+        None => Lockup::default(),
+    };
+
+    // Delegating needs the minimum delegation on top of the rent reserve
+    let reserve = rent_exempt_reserve();
+    let required = if vote.is_some() { reserve + MINIMUM_DELEGATION_LAMPORTS } else { reserve };
+    if data.lamports < required {
+        return Err(format!("Stake account needs at least {} lamports", required));
+    }
+
+    let (stake, mut instructions) = match (&data.stake, &data.seed) {
+        (Some(stake), None) => {
+            let stake = parse_pubkey(stake, "stake")?;
+            (stake, stake_instruction::create_account(&from, &stake, &authorized, &lockup, data.lamports))
+        }
+        (None, Some(seed)) => {
+            let base = optional_pubkey(data.base.as_deref(), "base")?.unwrap_or(from);
+            let stake = Pubkey::create_with_seed(&base, seed, &solana_stake_interface::program::ID)
+                .map_err(|_| "Seed must be at most 32 bytes".to_string())?;
+            (stake, stake_instruction::create_account_with_seed(&from, &stake, &base, seed, &authorized, &lockup, data.lamports))
+        }
+        _ => return Err("Provide exactly one of stake or seed".to_string()),
+    };
+
+    if let Some(vote) = vote {
+        instructions.push(stake_instruction::delegate_stake(&stake, &authorized.staker, &vote));
+    }
+
+    Ok(StakeData {
+        stake_account: Some(stake.to_string()),
+        rent_exempt_reserve: Some(reserve),
+        minimum_delegation: Some(MINIMUM_DELEGATION_LAMPORTS),
+        ..instructions.into()
+    })
+}
+
+pub fn delegate(content: &str) -> JsonResponse {
+    respond(parse_request(content).and_then(|data: DelegateRequest| {
+        let stake = parse_pubkey(&data.stake, "stake")?;
+        let staker = parse_pubkey(&data.staker, "staker")?;
+        let vote = parse_pubkey(&data.vote, "vote account")?;
+        Ok(vec![stake_instruction::delegate_stake(&stake, &staker, &vote)].into())
+    }))
+}
+
+pub fn deactivate(content: &str) -> JsonResponse {
+    respond(parse_request(content).and_then(|data: DeactivateRequest| {
+        let stake = parse_pubkey(&data.stake, "stake")?;
+        let staker = parse_pubkey(&data.staker, "staker")?;
+        Ok(vec![stake_instruction::deactivate_stake(&stake, &staker)].into())
+    }))
+}
+
+pub fn withdraw(content: &str) -> JsonResponse {
+    respond(parse_request(content).and_then(|data: WithdrawRequest| {
+        let stake = parse_pubkey(&data.stake, "stake")?;
+        let withdrawer = parse_pubkey(&data.withdrawer, "withdrawer")?;
+        let to = parse_pubkey(&data.to, "recipient")?;
+        let custodian = optional_pubkey(data.custodian.as_deref(), "custodian")?;
+        if data.lamports == 0 {
+            return Err("Withdraw amount must be greater than zero".to_string());
+        }
+        Ok(vec![stake_instruction::withdraw(&stake, &withdrawer, &to, data.lamports, custodian.as_ref())].into())
+    }))
+}
+
+pub fn split(content: &str) -> JsonResponse {
+    respond(parse_request(content).and_then(|data: SplitRequest| {
+        let stake = parse_pubkey(&data.stake, "stake")?;
+        let staker = parse_pubkey(&data.staker, "staker")?;
+        let split_stake = parse_pubkey(&data.split_stake, "split stake")?;
+        if stake == split_stake {
+            return Err("Split stake account must differ from the source".to_string());
+        }
+        if data.lamports == 0 {
+            return Err("Split amount must be greater than zero".to_string());
+        }
+        Ok(StakeData {
+            rent_exempt_reserve: Some(rent_exempt_reserve()),
+            ..stake_instruction::split(&stake, &staker, data.lamports, &split_stake).into()
+        })
+    }))
+}
+
+pub fn merge(content: &str) -> JsonResponse {
+    respond(parse_request(content).and_then(|data: MergeRequest| {
+        let destination = parse_pubkey(&data.destination, "destination")?;
+        let source = parse_pubkey(&data.source, "source")?;
+        let staker = parse_pubkey(&data.staker, "staker")?;
+        if destination == source {
+            return Err("Cannot merge a stake account into itself".to_string());
+        }
+        Ok(stake_instruction::merge(&destination, &source, &staker).into())
+    }))
+}
+
+pub fn authorize(content: &str) -> JsonResponse {
+    respond(parse_request(content).and_then(|data: AuthorizeRequest| {
+        let stake = parse_pubkey(&data.stake, "stake")?;
+        let authority = parse_pubkey(&data.authority, "authority")?;
+        let new_authority = parse_pubkey(&data.new_authority, "new authority")?;
+        let custodian = optional_pubkey(data.custodian.as_deref(), "custodian")?;
+        let stake_authorize = match data.authorize_type.as_str() {
+            "staker" => StakeAuthorize::Staker,
+            "withdrawer" => StakeAuthorize::Withdrawer,
+            other => return Err(format!("Unsupported authorizeType: {}", other)),
+        };
+
+        // The checked variant also requires the new authority to sign
+        let ix = if data.checked {
+            stake_instruction::authorize_checked(&stake, &authority, &new_authority, stake_authorize, custodian.as_ref())
+        } else {
+            stake_instruction::authorize(&stake, &authority, &new_authority, stake_authorize, custodian.as_ref())
+        };
+        Ok(vec![ix].into())
+    }))
+}